@@ -1,12 +1,15 @@
 use serde::{Deserialize, Serialize};
 use std::{collections::VecDeque, fmt::Display};
 
+use crate::game::note::Template;
+
 //use crate::strategy::{self, Strategy};
 
 mod io {
     use std::io::Write;
 
     use super::Deck;
+    use crate::error::{Error, Result};
     use serde::Serialize;
     use serde_json;
     pub enum Serialization {
@@ -14,11 +17,7 @@ mod io {
         Yaml,
     }
     impl Serialization {
-        pub fn write<T: Serialize>(
-            &self,
-            deck: &Deck<T>,
-            writer: impl Write,
-        ) -> std::io::Result<()> {
+        pub fn write<T: Serialize>(&self, deck: &Deck<T>, writer: impl Write) -> Result<()> {
             let res = match self {
                 Self::Json => Self::write_json(deck, writer),
                 Self::Yaml => Self::write_yaml(deck, writer),
@@ -26,18 +25,18 @@ mod io {
             res
         }
 
-        fn write_json<T: Serialize>(deck: &Deck<T>, writer: impl Write) -> std::io::Result<()> {
+        fn write_json<T: Serialize>(deck: &Deck<T>, writer: impl Write) -> Result<()> {
             let res = serde_json::to_writer(writer, deck);
             match res {
-                Err(e) => Err(std::io::Error::other(e)),
+                Err(e) => Err(Error::from(std::io::Error::from(e))),
                 _ => Ok(()),
             }
         }
 
-        fn write_yaml<T: Serialize>(deck: &Deck<T>, writer: impl Write) -> std::io::Result<()> {
+        fn write_yaml<T: Serialize>(deck: &Deck<T>, writer: impl Write) -> Result<()> {
             let res = serde_json::to_writer(writer, deck);
             match res {
-                Err(e) => Err(std::io::Error::other(e)),
+                Err(e) => Err(Error::from(std::io::Error::from(e))),
                 _ => Ok(()),
             }
         }
@@ -65,14 +64,87 @@ impl<T, U> Card<T, U> {
 #[derive(Serialize, Deserialize, Clone)]
 pub struct Deck<T> {
     title: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     author: Option<String>,
+    /// Render the faces of the cards as markdown.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    markdown: bool,
+    /// Overrides the daily limit on new cards from the config.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    new_per_day: Option<usize>,
+    /// Overrides the daily limit on reviews from the config.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    reviews_per_day: Option<usize>,
+    /// Tags shared by every card of the deck.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    tags: Vec<String>,
+    /// Names of the fields every note of the deck has.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    fields: Vec<String>,
+    /// How notes are turned into cards.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    templates: Vec<Template>,
     cards: Vec<T>,
 }
 
 impl<T> Deck<T> {
+    pub fn new(title: &str) -> Self {
+        Self {
+            title: title.to_string(),
+            author: None,
+            markdown: false,
+            new_per_day: None,
+            reviews_per_day: None,
+            tags: vec![],
+            fields: vec![],
+            templates: vec![],
+            cards: vec![],
+        }
+    }
+
     pub fn title(&self) -> &str {
         self.title.as_str()
     }
+
+    pub fn set_title(&mut self, title: &str) {
+        self.title = title.to_string();
+    }
+
+    pub fn markdown(&self) -> bool {
+        self.markdown
+    }
+
+    pub fn set_markdown(&mut self, markdown: bool) {
+        self.markdown = markdown;
+    }
+
+    pub fn new_per_day(&self) -> Option<usize> {
+        self.new_per_day
+    }
+
+    pub fn reviews_per_day(&self) -> Option<usize> {
+        self.reviews_per_day
+    }
+
+    pub fn tags(&self) -> &[String] {
+        &self.tags
+    }
+
+    pub fn set_tags(&mut self, tags: Vec<String>) {
+        self.tags = tags;
+    }
+
+    pub fn fields(&self) -> &[String] {
+        &self.fields
+    }
+
+    pub fn templates(&self) -> &[Template] {
+        &self.templates
+    }
+
+    pub fn cards_mut(&mut self) -> &mut Vec<T> {
+        &mut self.cards
+    }
     pub fn cards(&self) -> Vec<&T> {
         self.cards.iter().collect()
     }
@@ -80,6 +152,21 @@ impl<T> Deck<T> {
     pub fn into_cards(self) -> Vec<T> {
         self.cards
     }
+
+    /// Swaps the cards out while keeping the rest of the deck.
+    pub fn with_cards<U>(self, cards: Vec<U>) -> Deck<U> {
+        Deck {
+            title: self.title,
+            author: self.author,
+            markdown: self.markdown,
+            new_per_day: self.new_per_day,
+            reviews_per_day: self.reviews_per_day,
+            tags: self.tags,
+            fields: self.fields,
+            templates: self.templates,
+            cards,
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
@@ -0,0 +1,279 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+use serde_json::{Map, Value};
+
+use crate::app::Deck;
+use crate::error::{line_column, Error, Result};
+use crate::game::Kadeu;
+use crate::io::FileType;
+use crate::Entry;
+
+// faces longer than this have to be scrolled through on a regular terminal
+const LONG_FACE: usize = 1000;
+
+const DECK_FIELDS: [&str; 9] = [
+    "title",
+    "author",
+    "markdown",
+    "new_per_day",
+    "reviews_per_day",
+    "tags",
+    "fields",
+    "templates",
+    "cards",
+];
+const TEMPLATE_FIELDS: [&str; 5] = ["front", "back", "reversible", "hint", "notes"];
+
+/// Something wrong with a deck file, found by `check`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Problem {
+    pub location: Location,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Location {
+    Deck,
+    /// Where the file stopped parsing, both counted from 1.
+    Position {
+        line: usize,
+        column: usize,
+    },
+    /// The entry of the deck, counted from 1.
+    Card(usize),
+}
+
+impl Problem {
+    fn new(location: Location, message: String) -> Self {
+        Self { location, message }
+    }
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.location {
+            Location::Deck => write!(f, "{}", self.message),
+            Location::Position { line, column } => {
+                write!(f, "{}:{}: {}", line, column, self.message)
+            }
+            Location::Card(card) => write!(f, "card {}: {}", card, self.message),
+        }
+    }
+}
+
+/// Reads a deck file in any of the supported formats and lists its problems.
+pub fn check(path: &Path) -> Result<Vec<Problem>> {
+    let format = FileType::detect(path)?;
+    let text = fs::read_to_string(path).map_err(Error::io(path))?;
+    Ok(check_text(&text, &format))
+}
+
+fn check_text(text: &str, format: &FileType) -> Vec<Problem> {
+    let parsed = match format {
+        FileType::Json(_) => serde_json::from_str::<Deck<Entry>>(text)
+            .map_err(|e| position(e.line(), e.column(), &e))
+            .and_then(|deck| Ok((deck, serde_json::from_str(text).map_err(|e| at_deck(&e))?))),
+        FileType::Yaml(_) => serde_yaml::from_str::<Deck<Entry>>(text)
+            .map_err(|e| match e.location() {
+                Some(location) => position(location.line(), location.column(), &e),
+                None => at_deck(&e),
+            })
+            .and_then(|deck| Ok((deck, serde_yaml::from_str(text).map_err(|e| at_deck(&e))?))),
+        FileType::Toml(_) => toml::from_str::<Deck<Entry>>(text)
+            .map_err(|e| match e.span() {
+                Some(span) => {
+                    let (line, column) = line_column(text, span.start);
+                    position(line, column, e.message())
+                }
+                None => at_deck(&e),
+            })
+            .and_then(|deck| Ok((deck, toml::from_str(text).map_err(|e| at_deck(&e))?))),
+    };
+    match parsed {
+        Ok((deck, raw)) => check_deck(&deck, &raw),
+        Err(problem) => match raw(text, format).map(|raw| entries(&raw)) {
+            // serde can't say which part of a card is wrong, but it can say which card
+            Some(problems) if !problems.is_empty() => problems,
+            _ => vec![problem],
+        },
+    }
+}
+
+/// The file as plain values, if it is well formed at all.
+fn raw(text: &str, format: &FileType) -> Option<Value> {
+    match format {
+        FileType::Json(_) => serde_json::from_str(text).ok(),
+        FileType::Yaml(_) => serde_yaml::from_str(text).ok(),
+        FileType::Toml(_) => toml::from_str(text).ok(),
+    }
+}
+
+/// The cards which aren't any kind of entry.
+fn entries(raw: &Value) -> Vec<Problem> {
+    let cards = raw["cards"].as_array().into_iter().flatten();
+    cards
+        .enumerate()
+        .filter(|(_, card)| serde_json::from_value::<Entry>((*card).clone()).is_err())
+        .map(|(i, _)| {
+            let message = "isn't a flashcard, cloze, note or multiple choice card";
+            Problem::new(Location::Card(i + 1), message.to_string())
+        })
+        .collect()
+}
+
+fn position(line: usize, column: usize, error: impl fmt::Display) -> Problem {
+    // the position is shown on its own
+    let message = error.to_string();
+    let message = match message.rsplit_once(" at line ") {
+        Some((message, _)) => message.to_string(),
+        None => message,
+    };
+    Problem::new(Location::Position { line, column }, message)
+}
+
+fn at_deck(error: &impl fmt::Display) -> Problem {
+    Problem::new(Location::Deck, error.to_string())
+}
+
+/// Looks over a deck that parsed, `raw` is the same deck as plain values
+/// for the fields serde would have skipped over.
+fn check_deck(deck: &Deck<Entry>, raw: &Value) -> Vec<Problem> {
+    let mut problems = vec![];
+    let unknown = |object: Option<&Map<String, Value>>, known: &[&str]| -> Vec<String> {
+        object
+            .into_iter()
+            .flat_map(|object| object.keys())
+            .filter(|key| !known.contains(&key.as_str()))
+            .map(|key| format!("unknown field `{}`", key))
+            .collect()
+    };
+
+    for message in unknown(raw.as_object(), &DECK_FIELDS) {
+        problems.push(Problem::new(Location::Deck, message));
+    }
+    for (i, template) in deck.templates().iter().enumerate() {
+        let raw = &raw["templates"][i];
+        for message in unknown(raw.as_object(), &TEMPLATE_FIELDS) {
+            problems.push(Problem::new(
+                Location::Deck,
+                format!("template {}: {}", i + 1, message),
+            ));
+        }
+        if deck.fields().is_empty() {
+            continue;
+        }
+        for field in template.fields() {
+            if !deck.fields().iter().any(|known| known == field) {
+                problems.push(Problem::new(
+                    Location::Deck,
+                    format!("template {}: `{}` isn't one of the fields", i + 1, field),
+                ));
+            }
+        }
+    }
+
+    // the first entry each card was made from, by card id
+    let mut seen: HashMap<String, usize> = HashMap::new();
+    for (i, entry) in deck.cards().into_iter().enumerate() {
+        let card = Location::Card(i + 1);
+        let mut problem = |message: String| problems.push(Problem::new(card, message));
+        let known: &[&str] = match entry {
+            Entry::Choice(_) => &["question", "answer", "distractors", "hint", "notes", "tags"],
+            Entry::Cloze(_) => &["cloze", "hint", "notes", "tags"],
+            Entry::Note(_) => &["id", "note", "tags"],
+            Entry::Flashcard(_) => &[
+                "id",
+                "front",
+                "back",
+                "reversible",
+                "choices",
+                "hint",
+                "notes",
+                "tags",
+            ],
+        };
+        for message in unknown(raw["cards"][i].as_object(), known) {
+            problem(message);
+        }
+        match entry {
+            Entry::Cloze(cloze) => cloze.problems().into_iter().for_each(&mut problem),
+            Entry::Note(note) if !deck.fields().is_empty() => {
+                for (name, _) in note.fields() {
+                    if !deck.fields().contains(name) {
+                        problem(format!("`{}` isn't one of the fields of the deck", name));
+                    }
+                }
+            }
+            _ => {}
+        }
+
+        for flashcard in deck.expand_entry(entry) {
+            let faces = [("front", flashcard.front()), ("back", flashcard.back())];
+            for (side, face) in faces {
+                if face.trim().is_empty() {
+                    problem(format!("the {} is empty", side));
+                } else if face.chars().count() > LONG_FACE {
+                    problem(format!("the {} is too long to fit on screen", side));
+                }
+            }
+            match seen.get(&flashcard.id()) {
+                Some(first) if *first != i + 1 => {
+                    problem(format!("repeats card {}", first));
+                }
+                Some(_) => {}
+                None => {
+                    seen.insert(flashcard.id(), i + 1);
+                }
+            }
+        }
+    }
+    problems
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::{check_text, Location};
+    use crate::io::FileType;
+
+    #[test]
+    fn problems_are_found_by_card() {
+        let deck = r#"{
+            "title": "networking",
+            "colour": "blue",
+            "cards": [
+                {"front": "SYN", "back": "synchronize"},
+                {"front": "SYN", "back": "", "remarks": "?"},
+                {"cloze": "{{c1::TCP}} and {{c2:UDP}}"}
+            ]
+        }"#;
+        let problems = check_text(deck, &FileType::Json(PathBuf::new()));
+        let found: Vec<String> = problems.iter().map(|problem| problem.to_string()).collect();
+        assert_eq!(
+            found,
+            vec![
+                "unknown field `colour`",
+                "card 2: unknown field `remarks`",
+                "card 2: the back is empty",
+                "card 2: repeats card 1",
+                "card 3: `{{c2:UDP}}` isn't closed or numbered",
+            ]
+        );
+
+        let broken = "{\n  \"title\": \"networking\",\n  \"cards\": [\n    {\"front\": 1}\n  ]\n}";
+        let problems = check_text(broken, &FileType::Json(PathBuf::new()));
+        assert_eq!(problems[0].location, Location::Card(1));
+        let problems = check_text("{\n  \"title\": }", &FileType::Json(PathBuf::new()));
+        assert_eq!(
+            problems[0].location,
+            Location::Position {
+                line: 2,
+                column: 12
+            }
+        );
+    }
+}
@@ -1,10 +1,15 @@
 use std::ffi::OsStr;
-use std::{default, env, fs::OpenOptions, io::Read, path::PathBuf};
+use std::str::FromStr;
+use std::{default, env, fs::OpenOptions, io::ErrorKind, io::Read, path::PathBuf};
 use toml;
 
+use chrono::NaiveDate;
 use clap::{self, Arg, Parser};
 use serde::{Deserialize, Serialize};
 
+use crate::app::Deck;
+use crate::error::{self, line_column, Error};
+use crate::game::{schedule::Limits, DirectionMode, Score};
 use crate::ui::KadeuApp;
 
 #[cfg(target_os = "linux")]
@@ -18,27 +23,6 @@ mod fs {
     // [ ] TODO just add mod fs
 }
 
-#[derive(Debug, Clone, PartialEq)]
-enum Error<'a> {
-    NoHome,
-    Unkown(&'a str),
-}
-
-impl<'a> Into<std::io::Error> for Error<'a> {
-    fn into(self) -> std::io::Error {
-        match self {
-            Self::NoHome => std::io::Error::other("the home directory was unable to be sourced"),
-            Self::Unkown(err) => std::io::Error::other(err),
-        }
-    }
-}
-
-impl<'a> Default for Error<'a> {
-    fn default() -> Self {
-        Self::Unkown("an unkown error has occured :(")
-    }
-}
-
 #[derive(Parser, Debug)]
 #[command(version, about)]
 pub struct Args {
@@ -46,13 +30,28 @@ pub struct Args {
     pub debug: bool,
     #[arg(long, short)]
     config: Option<PathBuf>,
+    /// Seed for the session, decides how multiple choice options are dealt.
+    #[arg(long)]
+    pub seed: Option<u64>,
+    /// Which way round cards are studied.
+    #[arg(long, value_enum, default_value_t, global = true)]
+    pub direction: DirectionMode,
+    /// Study every card, not just the ones that are due.
+    #[arg(long, global = true)]
+    pub all: bool,
+    /// Only study cards with one of these tags, the tags of their deck count too.
+    #[arg(long = "tag", global = true)]
+    pub tags: Vec<String>,
+    /// Leave out cards with any of these tags.
+    #[arg(long = "exclude-tag", global = true)]
+    pub exclude_tags: Vec<String>,
     #[command(subcommand)]
     pub subcommand: Option<Subcommand>,
 }
 
 impl Args {
-    pub fn read_config(&self) -> std::io::Result<Config> {
-        let path = if let Some(path) = &self.config {
+    pub fn read_config(&self) -> error::Result<Config> {
+        let path: PathBuf = if let Some(path) = &self.config {
             path.clone()
         } else {
             // ?? not meant to be run like this...
@@ -61,42 +60,246 @@ impl Args {
         };
 
         let mut buf = String::new();
-        let _ = OpenOptions::new()
+        let read = OpenOptions::new()
             .read(true)
-            .open(path)?
-            .read_to_string(&mut buf)?;
-
-        match toml::from_str::<Config>(&buf) {
-            Ok(config) => Ok(config),
-            Err(e) => {
-                let message = e.message();
-                let format = format!("Error parsing configuration file: {}", message);
-                Err(std::io::Error::other(format))
-            }
+            .open(&path)
+            .and_then(|mut file| file.read_to_string(&mut buf));
+        if let Err(e) = read {
+            let message = match e.kind() {
+                ErrorKind::NotFound => {
+                    "wasn't found, make one or pass another with --config".to_string()
+                }
+                _ => format!("couldn't be read: {}", e),
+            };
+            return Err(Error::Config { path, message });
         }
+
+        toml::from_str::<Config>(&buf).map_err(|e| {
+            let message = match e.span() {
+                Some(span) => {
+                    let (line, column) = line_column(&buf, span.start);
+                    format!("isn't valid at {}:{}: {}", line, column, e.message())
+                }
+                None => format!("isn't valid: {}", e.message()),
+            };
+            Error::Config { path, message }
+        })
     }
 }
 
 #[derive(clap::Subcommand, Debug, Clone, Default)]
 pub enum Subcommand {
-    Show,
+    /// List the imported decks and collections.
+    Show {
+        /// Go through every collection, not just the top level.
+        #[arg(long)]
+        tree: bool,
+        /// Include the title, number of cards, format and what is due today.
+        #[arg(long)]
+        long: bool,
+        /// Print the listing as json, with every detail.
+        #[arg(long)]
+        json: bool,
+    },
+    /// Import decks, whole directories or globs such as `'decks/**/*.json'`,
+    /// keeping the collections they are in. Every deck is checked before it is copied.
     Import {
-        path: PathBuf,
+        #[arg(required = true)]
+        paths: Vec<PathBuf>,
+        /// The collection to import into, e.g. `languages/german`.
+        #[arg(long)]
+        into: Option<String>,
+        /// What to do with a deck that has already been imported.
+        #[arg(long, value_enum, default_value_t)]
+        on_conflict: OnConflict,
     },
+    /// Print the configuration in use, defaults filled in.
     Config,
+    /// List every deck with its new, learning and due cards for today.
     #[default]
+    Home,
     Browse,
+    /// Study the cards of one or more deck files together.
     Source {
-        path: PathBuf,
+        #[arg(required = true)]
+        paths: Vec<PathBuf>,
+    },
+    /// Study the cards of every imported deck which match a query,
+    /// e.g. `deck:networking/* missed:7 -tag:trivia`.
+    Study {
+        #[arg(long, short)]
+        query: String,
+        /// Keep the query as a filtered deck in the import directory under this name.
+        #[arg(long)]
+        save: Option<String>,
     },
+    /// Study imported decks by name, e.g. `networking/tcp`.
     Run {
-        name: String,
+        #[arg(required = true)]
+        names: Vec<String>,
+        /// Ask on standard input and print on standard output, without the full screen interface.
+        #[arg(long)]
+        plain: bool,
+    },
+    /// Edit a deck, creating it if the file doesn't exist yet.
+    /// The format is picked from the extension (json, yaml or toml).
+    Edit {
+        path: PathBuf,
+    },
+    /// Look for problems in decks, or in every deck under a directory.
+    /// Exits with a failure if there are any.
+    Check {
+        #[arg(required = true)]
+        paths: Vec<PathBuf>,
+    },
+    /// Merge decks into one which keeps the settings of the first deck.
+    /// Reviews of the cards that are kept move over to the merged deck.
+    Merge {
+        #[arg(required = true)]
+        decks: Vec<PathBuf>,
+        #[arg(long, short)]
+        output: PathBuf,
+        /// Leave out cards which are the same, or nearly the same, as another card.
+        #[arg(long)]
+        dedupe: bool,
+        /// How alike two cards have to be to count as duplicates, from 0 to 1.
+        #[arg(long, default_value_t = 0.85)]
+        similarity: f64,
+        /// Which of two duplicates is kept.
+        #[arg(long, value_enum, default_value_t)]
+        keep: Keep,
+    },
+    /// Break a deck into a collection of smaller decks, a directory named after the deck
+    /// unless given. Card ids and review history stay the same.
+    Split {
+        deck: PathBuf,
+        /// `tag` for a deck per tag, or `chunk=N` for decks of N entries.
+        #[arg(long)]
+        by: SplitBy,
+        #[arg(long, short)]
+        output: Option<PathBuf>,
+    },
+    /// Move or rename an imported deck or collection, taking its review history along.
+    Mv {
+        from: PathBuf,
+        to: PathBuf,
     },
+    /// Reviews, retention, upcoming reviews and the hardest cards of a deck,
+    /// or of every imported deck. Decks can be paths or imported names.
+    #[command(args_conflicts_with_subcommands = true)]
+    Stats {
+        #[command(subcommand)]
+        command: Option<StatsCommand>,
+        deck: Option<PathBuf>,
+        #[arg(long, value_enum, default_value_t)]
+        format: Format,
+    },
+}
+
+#[derive(clap::Subcommand, Debug, Clone)]
+pub enum StatsCommand {
+    /// Print every review along with its deck and card, or write it to a file.
+    Export {
+        /// Only the reviews of this deck or collection.
+        deck: Option<PathBuf>,
+        #[arg(long, value_enum, default_value_t)]
+        format: ExportFormat,
+        /// Leave out reviews before this date, e.g. 2024-05-01.
+        #[arg(long)]
+        since: Option<NaiveDate>,
+        #[arg(long, short)]
+        output: Option<PathBuf>,
+    },
+}
+
+/// Which of two duplicate cards `kadeu merge --dedupe` keeps.
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Default)]
+pub enum Keep {
+    /// Ask about each pair.
+    #[default]
+    Ask,
+    /// The card from the deck given first.
+    First,
+    Last,
+    /// The card reviewed most often.
+    Reviewed,
+}
+
+/// What to do when an imported deck is already in the imports directory.
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Default)]
+pub enum OnConflict {
+    #[default]
+    Skip,
+    Overwrite,
+    /// Import it next to the other one under a new name.
+    Rename,
+    /// Add the cards the imported deck doesn't have yet.
+    Merge,
+}
+
+/// How `kadeu split` breaks a deck up.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SplitBy {
+    Tag,
+    Chunk(usize),
+}
+
+impl FromStr for SplitBy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once('=') {
+            None if s == "tag" => Ok(Self::Tag),
+            Some(("chunk", size)) => match size.parse() {
+                Ok(size) if size > 0 => Ok(Self::Chunk(size)),
+                _ => Err(format!("`{}` isn't a number of entries", size)),
+            },
+            _ => Err("expected `tag` or `chunk=N`".to_string()),
+        }
+    }
+}
+
+/// How exported records are written.
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Default)]
+pub enum ExportFormat {
+    #[default]
+    Csv,
+    Json,
+}
+
+/// How a report is shown.
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Default)]
+pub enum Format {
+    /// A dashboard in the terminal.
+    #[default]
+    Tui,
+    Text,
+    Json,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Config {
     import_directory: Option<PathBuf>,
+    #[serde(default)]
+    review_log: Option<PathBuf>,
+    /// The best grade a card can get once its hint has been shown.
+    #[serde(default)]
+    hint_grade: Option<Score>,
+    /// New cards studied from each deck a day, unless the deck sets its own.
+    #[serde(default)]
+    new_per_day: Option<usize>,
+    /// Reviews studied from each deck a day, unless the deck sets its own.
+    #[serde(default)]
+    reviews_per_day: Option<usize>,
+    /// New cards studied a day from all the decks together.
+    #[serde(default)]
+    global_new_per_day: Option<usize>,
+    /// Reviews studied a day from all the decks together.
+    #[serde(default)]
+    global_reviews_per_day: Option<usize>,
+    /// Reviews to get through every day, a notice is shown once they are done.
+    #[serde(default)]
+    daily_goal: Option<usize>,
     default_strategy: String,
     backend: Option<String>,
 }
@@ -117,12 +320,53 @@ impl Config {
             }
         }
     }
+
+    /// Where every review is recorded, defaults to reviews.jsonl next to the imports.
+    pub fn review_log(&self) -> PathBuf {
+        if let Some(path) = &self.review_log {
+            return path.clone();
+        }
+        let mut path = self.import_directory();
+        path.set_file_name("reviews.jsonl");
+        path
+    }
+
+    pub fn hint_grade(&self) -> Option<Score> {
+        self.hint_grade
+    }
+
+    pub fn daily_goal(&self) -> Option<usize> {
+        self.daily_goal
+    }
+
+    /// The daily limits of a deck, its own settings win over the configured ones.
+    pub fn limits<T>(&self, deck: &Deck<T>) -> Limits {
+        Limits {
+            new: deck.new_per_day().or(self.new_per_day),
+            reviews: deck.reviews_per_day().or(self.reviews_per_day),
+        }
+    }
+
+    /// The daily limits across every deck, applied after those of each deck.
+    pub fn global_limits(&self) -> Limits {
+        Limits {
+            new: self.global_new_per_day,
+            reviews: self.global_reviews_per_day,
+        }
+    }
 }
 
 impl Default for Config {
     fn default() -> Self {
         Self {
             import_directory: None,
+            review_log: None,
+            hint_grade: None,
+            new_per_day: None,
+            reviews_per_day: None,
+            global_new_per_day: None,
+            global_reviews_per_day: None,
+            daily_goal: None,
             default_strategy: "Random".to_string(),
             backend: Some("crossterm".to_string()),
         }
@@ -0,0 +1,183 @@
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::io::DECK_EXTENSIONS;
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// Everything that can go wrong in kadeu, worded for whoever is using it.
+#[derive(Debug)]
+pub enum Error {
+    /// The configuration file couldn't be read or isn't valid.
+    Config { path: PathBuf, message: String },
+    /// A file that didn't parse, with the line and column it stopped at when known.
+    Parse {
+        path: PathBuf,
+        position: Option<(usize, usize)>,
+        message: String,
+    },
+    /// Reading or writing a file failed, the path is missing when it isn't known.
+    Io {
+        path: Option<PathBuf>,
+        source: io::Error,
+    },
+    /// A file that isn't named like any of the formats decks are kept in.
+    Format(PathBuf),
+    /// A file or directory in the way of one about to be written.
+    Exists(PathBuf),
+    /// Something to import which isn't a deck, a directory or a glob.
+    Source(PathBuf),
+    /// A term of a query that doesn't say what it is meant to.
+    Query { term: String, message: String },
+    /// Some of the decks to import couldn't be, each one has been reported already.
+    Import(usize),
+}
+
+impl Error {
+    /// Wraps an error reading or writing the file at `path`.
+    pub fn io(path: &Path) -> impl FnOnce(io::Error) -> Self + '_ {
+        move |source| Self::Io {
+            path: Some(path.to_path_buf()),
+            source,
+        }
+    }
+
+    pub fn parse(
+        path: &Path,
+        position: Option<(usize, usize)>,
+        message: impl fmt::Display,
+    ) -> Self {
+        let message = message.to_string();
+        // the position is shown along with the path
+        let message = match message.rsplit_once(" at line ") {
+            Some((message, _)) if position.is_some() => message.to_string(),
+            _ => message,
+        };
+        Self::Parse {
+            path: path.to_path_buf(),
+            position,
+            message,
+        }
+    }
+}
+
+/// The line and column of a byte offset into the text, both counted from 1.
+pub fn line_column(text: &str, offset: usize) -> (usize, usize) {
+    let before = &text[..offset.min(text.len())];
+    let line = before.matches('\n').count() + 1;
+    let column = before.len() - before.rfind('\n').map_or(0, |i| i + 1) + 1;
+    (line, column)
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Config { path, message } => {
+                write!(f, "the configuration at {} {}", path.display(), message)
+            }
+            Self::Parse {
+                path,
+                position: Some((line, column)),
+                message,
+            } => write!(f, "{}:{}:{}: {}", path.display(), line, column, message),
+            Self::Parse { path, message, .. } => write!(f, "{}: {}", path.display(), message),
+            Self::Io { path, source } => {
+                let message = match source.kind() {
+                    io::ErrorKind::NotFound => "not found".to_string(),
+                    io::ErrorKind::PermissionDenied => "permission denied".to_string(),
+                    io::ErrorKind::AlreadyExists => "already exists".to_string(),
+                    _ => source.to_string(),
+                };
+                match path {
+                    Some(path) => write!(f, "{}: {}", path.display(), message),
+                    None => write!(f, "{}", message),
+                }
+            }
+            Self::Format(path) => write!(
+                f,
+                "{}: can't tell what format the deck is in, expected .{}",
+                path.display(),
+                DECK_EXTENSIONS.join(", .")
+            ),
+            Self::Exists(path) => write!(f, "{}: already exists", path.display()),
+            Self::Source(path) => {
+                write!(f, "{}: not a deck, directory or glob", path.display())
+            }
+            Self::Query { term, message } => write!(f, "`{}` in the query: {}", term, message),
+            Self::Import(1) => write!(f, "1 deck could not be imported"),
+            Self::Import(failed) => write!(f, "{} decks could not be imported", failed),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+// the apps and most of the commands work in io results, errors pass through them
+// wrapped and are unwrapped again when they are shown
+impl From<Error> for io::Error {
+    fn from(error: Error) -> Self {
+        match error {
+            Error::Io { path: None, source } => source,
+            error => io::Error::other(error),
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(error: io::Error) -> Self {
+        if error.get_ref().is_none() {
+            return Self::Io {
+                path: None,
+                source: error,
+            };
+        }
+        let kind = error.kind();
+        match error.into_inner().map(|inner| inner.downcast::<Error>()) {
+            Some(Ok(error)) => *error,
+            Some(Err(inner)) => Self::Io {
+                path: None,
+                source: io::Error::new(kind, inner),
+            },
+            None => Self::Io {
+                path: None,
+                source: kind.into(),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+    use std::path::{Path, PathBuf};
+
+    use super::{line_column, Error};
+
+    #[test]
+    fn errors_survive_io_results() {
+        let error = Error::parse(
+            Path::new("deck.json"),
+            Some((2, 12)),
+            "expected value at line 2 column 12",
+        );
+        let error = Error::from(io::Error::from(error));
+        assert_eq!(error.to_string(), "deck.json:2:12: expected value");
+
+        let missing = io::Error::from(io::ErrorKind::NotFound);
+        let error = Error::io(Path::new("deck.json"))(missing);
+        assert_eq!(error.to_string(), "deck.json: not found");
+        assert!(matches!(
+            Error::from(io::Error::from(Error::Format(PathBuf::from("deck.txt")))),
+            Error::Format(_)
+        ));
+        assert_eq!(line_column("{\n  \"title\": }", 12), (2, 11));
+    }
+}
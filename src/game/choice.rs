@@ -0,0 +1,156 @@
+use rand::{seq::SliceRandom, Rng};
+use serde::{Deserialize, Serialize};
+
+use super::{flashcard::Flashcard, Expand};
+
+/// How many options a card is dealt when its distractors come from the rest of the deck.
+pub const OPTIONS: usize = 4;
+
+/// A question with one correct answer to pick out from a set of distractors.
+/// Without distractors the wrong options are drawn from the other cards in the deck.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Choice {
+    question: String,
+    answer: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    distractors: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    hint: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    notes: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    tags: Vec<String>,
+}
+
+impl Choice {
+    pub fn new(question: &str, answer: &str) -> Self {
+        Self {
+            question: question.to_string(),
+            answer: answer.to_string(),
+            distractors: vec![],
+            hint: None,
+            notes: None,
+            tags: vec![],
+        }
+    }
+
+    pub fn question(&self) -> &str {
+        &self.question
+    }
+
+    pub fn answer(&self) -> &str {
+        &self.answer
+    }
+
+    pub fn distractors(&self) -> &[String] {
+        &self.distractors
+    }
+
+    pub fn set_question(&mut self, question: String) {
+        self.question = question;
+    }
+
+    pub fn set_answer(&mut self, answer: String) {
+        self.answer = answer;
+    }
+
+    pub fn set_distractors(&mut self, distractors: Vec<String>) {
+        self.distractors = distractors;
+    }
+
+    pub fn hint(&self) -> Option<&str> {
+        self.hint.as_deref()
+    }
+
+    pub fn notes(&self) -> Option<&str> {
+        self.notes.as_deref()
+    }
+
+    pub fn set_hint(&mut self, hint: Option<String>) {
+        self.hint = hint;
+    }
+
+    pub fn set_notes(&mut self, notes: Option<String>) {
+        self.notes = notes;
+    }
+
+    pub fn tags(&self) -> &[String] {
+        &self.tags
+    }
+
+    pub fn set_tags(&mut self, tags: Vec<String>) {
+        self.tags = tags;
+    }
+}
+
+impl Expand for Choice {
+    type Item = Flashcard<String, String>;
+
+    fn expand(&self) -> Vec<Self::Item> {
+        let mut choices = vec![self.answer.clone()];
+        choices.extend(self.distractors.iter().cloned());
+        let mut card = Flashcard::new(self.question.clone(), self.answer.clone())
+            .with_choices(choices)
+            .with_tags(self.tags.clone());
+        card.set_reversible(Some(false));
+        card.set_hint(self.hint.clone());
+        card.set_notes(self.notes.clone());
+        vec![card]
+    }
+}
+
+/// Gets multiple choice cards ready for a session.
+/// Cards which only offer their own answer are topped up with distractors from the pool,
+/// then the options of every card are shuffled.
+pub fn deal<T, U, R>(cards: &mut [Flashcard<T, U>], pool: &[U], rng: &mut R)
+where
+    U: Clone + PartialEq,
+    R: Rng,
+{
+    for card in cards.iter_mut() {
+        let choices = card.choices_mut();
+        if choices.is_empty() {
+            continue;
+        }
+
+        if choices.len() == 1 {
+            let mut candidates: Vec<&U> = vec![];
+            for back in pool {
+                if !choices.contains(back) && !candidates.contains(&back) {
+                    candidates.push(back);
+                }
+            }
+            let wanted = OPTIONS - 1;
+            let drawn: Vec<U> = candidates
+                .choose_multiple(rng, wanted)
+                .map(|back| (*back).clone())
+                .collect();
+            choices.extend(drawn);
+        }
+        choices.shuffle(rng);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{deal, Choice, OPTIONS};
+    use crate::game::{Expand, Kadeu};
+    use rand::{rngs::StdRng, SeedableRng};
+
+    #[test]
+    fn distractors_are_drawn_from_the_pool() {
+        let mut choice = Choice::new("2 + 2", "4");
+        choice.set_notes(Some("two pairs".to_string()));
+        let mut cards = choice.expand();
+        assert_eq!(cards[0].notes(), Some("two pairs"));
+        let pool: Vec<String> = ["1", "4", "5", "5", "22"].map(String::from).to_vec();
+        deal(&mut cards, &pool, &mut StdRng::seed_from_u64(7));
+
+        let choices = cards[0].choices();
+        assert_eq!(choices.len(), OPTIONS);
+        assert!(choices.contains(cards[0].back()));
+        for choice in choices {
+            assert_eq!(choices.iter().filter(|other| *other == choice).count(), 1);
+        }
+    }
+}
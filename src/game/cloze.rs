@@ -0,0 +1,234 @@
+use serde::{Deserialize, Serialize};
+
+use super::{flashcard::Flashcard, Expand};
+
+const OPEN: &str = "{{c";
+const CLOSE: &str = "}}";
+const SEPARATOR: &str = "::";
+
+/// A sentence with deletions marked as `{{c1::answer}}` or `{{c1::answer::hint}}`.
+/// Every cloze number becomes its own card.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Cloze {
+    cloze: String,
+    /// Shown on every card of the sentence, unlike the hints of single deletions.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    hint: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    notes: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    tags: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Segment<'a> {
+    Text(&'a str),
+    Deletion {
+        number: usize,
+        answer: &'a str,
+        hint: Option<&'a str>,
+    },
+}
+
+impl Cloze {
+    pub fn new(text: &str) -> Self {
+        Self {
+            cloze: text.to_string(),
+            hint: None,
+            notes: None,
+            tags: vec![],
+        }
+    }
+
+    pub fn text(&self) -> &str {
+        &self.cloze
+    }
+
+    pub fn set_text(&mut self, text: String) {
+        self.cloze = text;
+    }
+
+    pub fn hint(&self) -> Option<&str> {
+        self.hint.as_deref()
+    }
+
+    pub fn notes(&self) -> Option<&str> {
+        self.notes.as_deref()
+    }
+
+    pub fn set_hint(&mut self, hint: Option<String>) {
+        self.hint = hint;
+    }
+
+    pub fn set_notes(&mut self, notes: Option<String>) {
+        self.notes = notes;
+    }
+
+    pub fn tags(&self) -> &[String] {
+        &self.tags
+    }
+
+    pub fn set_tags(&mut self, tags: Vec<String>) {
+        self.tags = tags;
+    }
+
+    /// The distinct cloze numbers in the order they first appear.
+    pub fn numbers(&self) -> Vec<usize> {
+        let mut numbers = vec![];
+        for segment in segments(&self.cloze) {
+            if let Segment::Deletion { number, .. } = segment {
+                if !numbers.contains(&number) {
+                    numbers.push(number);
+                }
+            }
+        }
+        numbers
+    }
+
+    /// What is wrong with the markup, markers which aren't closed or numbered
+    /// and a text without any deletions.
+    pub fn problems(&self) -> Vec<String> {
+        let mut problems = vec![];
+        for (start, _) in self.cloze.match_indices(OPEN) {
+            let marker = &self.cloze[start + OPEN.len()..];
+            let parsed = marker.find(CLOSE).and_then(|end| deletion(&marker[..end]));
+            if parsed.is_none() {
+                let shown: String = self.cloze[start..].chars().take(16).collect();
+                problems.push(format!("`{}` isn't closed or numbered", shown));
+            }
+        }
+        if self.numbers().is_empty() {
+            problems.push("has no deletions".to_string());
+        }
+        problems
+    }
+
+    /// The card for one cloze number, the front hides that number's deletions
+    /// and the back shows the whole sentence.
+    pub fn card(&self, active: usize) -> Flashcard<String, String> {
+        let mut front = String::new();
+        let mut back = String::new();
+        for segment in segments(&self.cloze) {
+            match segment {
+                Segment::Text(text) => {
+                    front.push_str(text);
+                    back.push_str(text);
+                }
+                Segment::Deletion {
+                    number,
+                    answer,
+                    hint,
+                } => {
+                    if number == active {
+                        front.push_str(&format!("[{}]", hint.unwrap_or("...")));
+                    } else {
+                        front.push_str(answer);
+                    }
+                    back.push_str(answer);
+                }
+            }
+        }
+        let mut card = Flashcard::new(front, back).with_tags(self.tags.clone());
+        // the back repeats the front, there is nothing to learn the other way round
+        card.set_reversible(Some(false));
+        card.set_hint(self.hint.clone());
+        card.set_notes(self.notes.clone());
+        card
+    }
+}
+
+impl Expand for Cloze {
+    type Item = Flashcard<String, String>;
+
+    fn expand(&self) -> Vec<Self::Item> {
+        self.numbers()
+            .into_iter()
+            .map(|number| self.card(number))
+            .collect()
+    }
+}
+
+/// Splits the text into plain runs and deletions.
+/// Markers which aren't closed or numbered are left in as plain text.
+fn segments(text: &str) -> Vec<Segment<'_>> {
+    let mut segments = vec![];
+    let mut rest = text;
+
+    while let Some(start) = rest.find(OPEN) {
+        let marker = &rest[start + OPEN.len()..];
+        let parsed = marker
+            .find(CLOSE)
+            .and_then(|end| Some((deletion(&marker[..end])?, end)));
+        let Some((deletion, end)) = parsed else {
+            segments.push(Segment::Text(&rest[..start + OPEN.len()]));
+            rest = marker;
+            continue;
+        };
+        if start > 0 {
+            segments.push(Segment::Text(&rest[..start]));
+        }
+        segments.push(deletion);
+        rest = &marker[end + CLOSE.len()..];
+    }
+
+    if !rest.is_empty() {
+        segments.push(Segment::Text(rest));
+    }
+    segments
+}
+
+/// Parses the inside of a marker, `1::answer` or `1::answer::hint`.
+fn deletion(inner: &str) -> Option<Segment<'_>> {
+    if inner.contains("{{") {
+        return None;
+    }
+    let (number, content) = inner.split_once(SEPARATOR)?;
+    let number = number.parse().ok()?;
+    let (answer, hint) = match content.split_once(SEPARATOR) {
+        Some((answer, hint)) => (answer, Some(hint)),
+        None => (content, None),
+    };
+    Some(Segment::Deletion {
+        number,
+        answer,
+        hint,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Cloze;
+    use crate::game::{Expand, Kadeu};
+
+    #[test]
+    fn one_card_per_number() {
+        let cloze: Cloze = serde_json::from_str(
+            r#"{"cloze": "{{c1::Rust}} was first released in {{c2::2015::year}}, by {{c1::Mozilla}}",
+                "notes": "1.0 came out in May"}"#,
+        )
+        .unwrap();
+        let cards = cloze.expand();
+        assert_eq!(cards.len(), 2);
+        assert_eq!(cards[1].notes(), Some("1.0 came out in May"));
+        assert_eq!(
+            cards[0].front(),
+            "[...] was first released in 2015, by [...]"
+        );
+        assert_eq!(
+            cards[1].front(),
+            "Rust was first released in [year], by Mozilla"
+        );
+        assert_eq!(
+            cards[1].back(),
+            "Rust was first released in 2015, by Mozilla"
+        );
+    }
+
+    #[test]
+    fn broken_markers_stay_as_text() {
+        let cloze = Cloze::new("{{c1::open and {{cx::nope}} {{c2::ok}}");
+        assert_eq!(cloze.numbers(), vec![2]);
+        assert_eq!(cloze.card(2).back(), "{{c1::open and {{cx::nope}} ok");
+        assert_eq!(cloze.problems().len(), 2);
+    }
+}
@@ -1,6 +1,9 @@
-use super::Kadeu;
-use ratatui::text::Text;
-use std::{collections::VecDeque, fmt::Display};
+use super::{schedule::Due, Direction, Kadeu, Score};
+use std::{
+    collections::VecDeque,
+    fmt::Display,
+    time::{Duration, Instant},
+};
 
 type StrategyFunction<T> = fn(&mut VecDeque<T>) -> Option<T>;
 
@@ -38,6 +41,16 @@ mod strategies {
         items.pop_front()
     }
 
+    /// Takes turns between the decks the items came from, keeping the order within each deck.
+    pub fn interleave<T>(items: &mut VecDeque<super::Pin<T>>) -> Option<super::Pin<T>> {
+        let item = items.pop_front()?;
+        // the next deck in line goes to the front, this deck waits behind the others
+        if let Some(next) = items.iter().position(|other| other.deck != item.deck) {
+            items.rotate_left(next);
+        }
+        Some(item)
+    }
+
     pub fn random<T>(items: &mut VecDeque<T>) -> Option<T> {
         if items.is_empty() {
             return None;
@@ -47,37 +60,75 @@ mod strategies {
     }
 }
 
+/// An item on its way through the engine, along with how it is being shown.
 #[derive(Debug)]
-struct Pin<T> {
+pub struct Pin<T> {
     item: T,
+    direction: Direction,
+    // the deck the item came from, as named in the review log
+    deck: String,
     pinned: bool,
+    // the option picked on a multiple choice card
+    picked: Option<usize>,
+    score: Option<Score>,
+    hinted: bool,
+    // the notes are opened below the back
+    notes: bool,
+    // when the item was drawn and how long it took to reveal it
+    shown: Option<Instant>,
+    answer_time: Option<Duration>,
 }
 
-// This looks horendous
-impl<'a, K, T, U> Into<Text<'a>> for &Pin<K>
+impl<K, T, U> Pin<K>
 where
     K: Kadeu<Front = T, Back = U>,
     T: Display,
     U: Display,
 {
-    fn into(self) -> Text<'a> {
+    /// The side shown first, followed by the other side once it has been revealed.
+    fn faces(&self) -> (String, Option<String>) {
+        let (first, second) = match self.direction {
+            Direction::Forward => (self.item.front().to_string(), self.item.back().to_string()),
+            Direction::Backward => (self.item.back().to_string(), self.item.front().to_string()),
+        };
         if self.pinned {
-            self.item.front().to_string()
+            (first, None)
         } else {
-            self.item.back().to_string()
+            (first, Some(second))
         }
-        .into()
     }
 }
 
 impl<T> From<T> for Pin<T> {
     fn from(item: T) -> Self {
-        Self { item, pinned: true }
+        Self::new(Due {
+            deck: String::new(),
+            card: item,
+            direction: Direction::Forward,
+        })
     }
 }
 
 impl<T> Pin<T> {
+    fn new(due: Due<T>) -> Self {
+        Self {
+            item: due.card,
+            direction: due.direction,
+            deck: due.deck,
+            pinned: true,
+            picked: None,
+            score: None,
+            hinted: false,
+            notes: false,
+            shown: None,
+            answer_time: None,
+        }
+    }
+
     fn unpin(&mut self) {
+        if self.pinned {
+            self.answer_time = self.shown.map(|shown| shown.elapsed());
+        }
         self.pinned = false;
     }
     fn get_mut(&mut self) -> &mut T {
@@ -91,26 +142,27 @@ pub trait Strategy<T> {
 
 #[derive(Debug)]
 pub struct Engine<T> {
-    items: VecDeque<T>,
+    items: VecDeque<Pin<T>>,
     current: Option<Pin<T>>,
-    strategy: StrategyFunction<T>,
+    strategy: StrategyFunction<Pin<T>>,
 }
 
 impl<T> Engine<T> {
-    pub fn new(items: VecDeque<T>, strat: StrategyFunction<T>) -> Self {
+    pub fn new(items: VecDeque<Due<T>>, strat: StrategyFunction<Pin<T>>) -> Self {
         // let strategy = U::new();
         Self {
-            items,
+            items: items.into_iter().map(Pin::new).collect(),
             current: None,
             strategy: strat,
         }
     }
 
+    /// Draws the next item, the current item is cleared once the items run out.
     pub fn next(&mut self) {
-        if let Some(item) = (self.strategy)(&mut self.items) {
-            self.current = Some(item.into());
-        };
-        // self.current()
+        self.current = (self.strategy)(&mut self.items);
+        if let Some(current) = self.current.as_mut() {
+            current.shown = Some(Instant::now());
+        }
     }
 
     pub fn unpin_current(&mut self) {
@@ -139,132 +191,704 @@ impl<T> Engine<T> {
         self.items.is_empty()
     }
 
+    /// Swaps out the item currently on display, keeping whichever side is showing.
+    pub fn replace_current(&mut self, item: T) {
+        if let Some(current) = self.current.as_mut() {
+            current.item = item;
+        }
+    }
+
     pub fn add(&mut self, item: T) {
-        self.items.push_front(item);
+        self.items.push_front(Pin::from(item));
     }
 }
 
 pub mod ui {
-    use std::{collections::VecDeque, fmt::Display};
+    use std::{
+        collections::HashMap,
+        fmt::Display,
+        io::{BufRead, Write},
+        path::PathBuf,
+    };
 
+    use chrono::Local;
     use crossterm::event::KeyCode;
-    use ratatui::{text::Text, widgets::Block};
+    use ratatui::{
+        layout::{Constraint, Margin},
+        style::{Style, Stylize},
+        text::{Line, Text},
+        widgets::Block,
+        Frame,
+    };
 
     use super::{strategies, Engine};
     use crate::{
-        app::Deck,
-        game::Kadeu,
+        game::{
+            review::{Review, ReviewLog},
+            schedule::Due,
+            stats::{Day, Streak},
+            Kadeu, Score,
+        },
         ui::{
             center,
+            heatmap::Heatmap,
+            highlight,
             inputs::{Input, KeyMap},
+            markdown, render_scrollable,
             style::AppStyle,
-            Exit, KadeuApp,
+            Intent, KadeuApp, Outcome, Screen,
         },
     };
 
+    // Long lines get hard to follow on wide terminals.
+    const MAX_TEXT_WIDTH: u16 = 80;
+    // a year of weeks along with the weekday names
+    const CALENDAR_WIDTH: u16 = 4 + 53 * 2;
+
     pub struct FlashcardApp<T> {
         show_title: bool,
         show_end: bool,
         engine: Engine<T>,
         title: String,
+        // how far the text of an oversized card has been scrolled down
+        scroll: u16,
+        markdown: bool,
+        results: Vec<Score>,
+        // nothing was due when the session started
+        empty: bool,
+        // entries of the decks which made no cards at all
+        skipped: usize,
+        // where answers are recorded
+        log: Option<ReviewLog>,
+        // best grade left once the hint has been shown
+        hint_grade: Option<Score>,
+        // titles of the decks by the name they are logged under
+        decks: HashMap<String, String>,
+        // reviews a day up to today, counting the ones given in the session
+        calendar: Vec<Day>,
+        daily_goal: Option<usize>,
+        // the goal was met during the session
+        goal_met: bool,
+        show_calendar: bool,
     }
 
-    impl<T> From<Deck<T>> for FlashcardApp<T> {
-        fn from(deck: Deck<T>) -> Self {
+    impl<T> FlashcardApp<T> {
+        /// A session over the given cards, each shown in its own direction.
+        pub fn new(title: &str, markdown: bool, items: Vec<Due<T>>) -> Self {
+            // cards from several decks take turns, a single deck keeps its order
+            let strategy = match items.first() {
+                Some(first) if items.iter().any(|item| item.deck != first.deck) => {
+                    strategies::interleave
+                }
+                _ => strategies::linear,
+            };
             Self {
                 show_title: true,
                 show_end: false,
-                title: deck.title().to_string(),
+                scroll: 0,
+                markdown,
+                results: vec![],
+                empty: items.is_empty(),
+                skipped: 0,
+                log: None,
+                hint_grade: None,
+                title: title.to_string(),
+                decks: HashMap::new(),
+                calendar: vec![],
+                daily_goal: None,
+                goal_met: false,
+                show_calendar: false,
                 // TODO figure out where this strategy is sourced from??
-                engine: Engine::new(VecDeque::from(deck.into_cards()), strategies::linear),
+                engine: Engine::new(items.into(), strategy),
+            }
+        }
+
+        /// Records every answer given in the session to the log.
+        pub fn with_log(mut self, log: ReviewLog) -> Self {
+            self.log = Some(log);
+            self
+        }
+
+        /// Entries which made no cards, such as a cloze without deletions,
+        /// told of before the session starts.
+        pub fn with_skipped(mut self, skipped: usize) -> Self {
+            self.skipped = skipped;
+            self
+        }
+
+        fn skipped_notice(&self) -> Option<String> {
+            (self.skipped > 0).then(|| {
+                format!(
+                    "{} entries make no cards and were left out, see kadeu check",
+                    self.skipped
+                )
+            })
+        }
+
+        /// Names the deck each card came from, shown when the session has more than one.
+        pub fn with_deck_titles(mut self, decks: HashMap<String, String>) -> Self {
+            self.decks = decks;
+            self
+        }
+
+        /// Caps the grade of cards answered after their hint was shown.
+        pub fn with_hint_grade(mut self, grade: Option<Score>) -> Self {
+            self.hint_grade = grade;
+            self
+        }
+
+        /// The reviews given each day so far, oldest first and ending today,
+        /// which can be looked at once the session is over.
+        pub fn with_calendar(mut self, calendar: Vec<Day>, daily_goal: Option<usize>) -> Self {
+            self.calendar = calendar;
+            self.daily_goal = daily_goal;
+            self
+        }
+
+        fn reviewed_today(&self) -> usize {
+            self.calendar.last().map_or(0, |today| today.count)
+        }
+
+        fn face(&self, content: &str, style: &AppStyle) -> Text<'static> {
+            if self.markdown {
+                markdown::render(content, style)
+            } else {
+                highlight::fenced(content, style)
+            }
+        }
+
+        pub fn current(&self) -> Option<&T> {
+            self.engine.current().map(|pin| &pin.item)
+        }
+
+        /// The deck the card on display came from.
+        pub fn current_deck(&self) -> Option<&str> {
+            self.engine.current().map(|pin| pin.deck.as_str())
+        }
+
+        pub fn replace_current(&mut self, item: T) {
+            self.engine.replace_current(item);
+        }
+
+        pub fn results(&self) -> &[Score] {
+            &self.results
+        }
+
+        fn next(&mut self) {
+            self.engine.next();
+            if self.engine.current().is_none() {
+                self.show_end = true;
+            }
+        }
+
+        fn summary(&self) -> String {
+            let hits = self
+                .results
+                .iter()
+                .filter(|score| **score != Score::Miss)
+                .count();
+            if self.empty {
+                "Nothing is due in this deck, come back later".to_string()
+            } else if self.results.is_empty() {
+                "End of deck".to_string()
+            } else {
+                format!("End of deck, {} of {} right", hits, self.results.len())
             }
         }
     }
 
-    impl<T, U, V> KadeuApp for FlashcardApp<T>
+    impl<T, U, V> FlashcardApp<T>
     where
         T: Kadeu<Front = U, Back = V>,
         U: Display,
         V: Display,
+    {
+        /// Grades the card on display, logs the answer and moves on to the next card.
+        fn record(&mut self, score: Score) -> std::io::Result<()> {
+            let Some(current) = self.engine.current() else {
+                return Ok(());
+            };
+            let score = match self.hint_grade {
+                Some(grade) if current.hinted => score.cap(grade),
+                _ => score,
+            };
+            if let Some(log) = &self.log {
+                log.append(&Review {
+                    deck: current.deck.clone(),
+                    card: current.item.id(),
+                    direction: current.direction,
+                    score,
+                    hinted: current.hinted,
+                    answer_ms: current.answer_time.map(|time| time.as_millis() as u64),
+                    reviewed_at: Local::now(),
+                })?;
+            }
+            self.results.push(score);
+            if let Some(today) = self.calendar.last_mut() {
+                today.count += 1;
+                self.goal_met |= self.daily_goal == Some(today.count);
+            }
+            self.next();
+            Ok(())
+        }
+
+        /// Picks an option of a multiple choice card and scores it.
+        fn pick(&mut self, index: usize) {
+            let Some(current) = self.engine.current.as_mut() else {
+                return;
+            };
+            if !current.pinned {
+                return;
+            }
+            let Some(option) = current.item.choices().get(index) else {
+                return;
+            };
+            let score = if option.to_string() == current.item.back().to_string() {
+                Score::Hit
+            } else {
+                Score::Miss
+            };
+            current.picked = Some(index);
+            current.score = Some(score);
+            current.unpin();
+        }
+
+        /// Runs the session line by line without taking over the terminal.
+        /// Each front is printed and answered with enter or a typed answer,
+        /// followed by the back and a grade. A line of `q` or the end of the input stops it.
+        pub fn run_plain(
+            &mut self,
+            mut input: impl BufRead,
+            mut output: impl Write,
+        ) -> std::io::Result<()> {
+            let mut read = |output: &mut dyn Write, prompt: &str| {
+                write!(output, "{}", prompt)?;
+                output.flush()?;
+                let mut line = String::new();
+                let read = input.read_line(&mut line)?;
+                let line = line.trim().to_string();
+                Ok::<_, std::io::Error>((read > 0 && line != "q").then_some(line))
+            };
+
+            writeln!(output, "{}", self.title)?;
+            if let Some(notice) = self.skipped_notice() {
+                writeln!(output, "{}", notice)?;
+            }
+            self.show_title = false;
+            self.next();
+            while let Some(current) = self.engine.current() {
+                let (front, _) = current.faces();
+                let choices = !current.item.choices().is_empty();
+                writeln!(output)?;
+                writeln!(output, "{}", front)?;
+                for (i, option) in current.item.choices().iter().enumerate() {
+                    writeln!(output, "{}) {}", i + 1, option)?;
+                }
+                let prompt = match (choices, current.item.hint().is_some()) {
+                    (true, _) => "pick> ",
+                    (false, true) => "answer (h for a hint)> ",
+                    (false, false) => "answer> ",
+                };
+                let mut typed = loop {
+                    let Some(line) = read(&mut output, prompt)? else {
+                        return Ok(());
+                    };
+                    let Some(current) = self.engine.current.as_mut() else {
+                        return Ok(());
+                    };
+                    match current.item.hint() {
+                        Some(hint) if line == "h" && !choices => {
+                            current.hinted = true;
+                            writeln!(output, "hint: {}", hint)?;
+                        }
+                        _ => break line,
+                    }
+                };
+
+                if choices {
+                    match typed.parse::<usize>() {
+                        Ok(n) if n > 0 => self.pick(n - 1),
+                        _ => {}
+                    }
+                    typed.clear();
+                }
+                let Some(current) = self.engine.current.as_mut() else {
+                    return Ok(());
+                };
+                if current.pinned {
+                    if choices {
+                        current.score = Some(Score::Miss);
+                    }
+                    current.unpin();
+                }
+                let (_, back) = current.faces();
+                writeln!(output, "{}", back.unwrap_or_default())?;
+                if let Some(notes) = current.item.notes() {
+                    writeln!(output, "notes: {}", notes)?;
+                }
+
+                let score = match current.score {
+                    Some(score) => {
+                        writeln!(output, "{}", score.to_string())?;
+                        score
+                    }
+                    None => {
+                        // a typed answer that matches the back is right unless graded otherwise
+                        let back = current.faces().1.unwrap_or_default();
+                        let right = typed.is_empty() || typed.eq_ignore_ascii_case(back.trim());
+                        let prompt = if right {
+                            "right? [Y/n]> "
+                        } else {
+                            "right? [y/N]> "
+                        };
+                        let Some(grade) = read(&mut output, prompt)? else {
+                            return Ok(());
+                        };
+                        match grade.to_lowercase().as_str() {
+                            "y" | "yes" => Score::Hit,
+                            "n" | "no" => Score::Miss,
+                            _ if right => Score::Hit,
+                            _ => Score::Miss,
+                        }
+                    }
+                };
+                self.record(score)?;
+            }
+            writeln!(output)?;
+            writeln!(output, "{}", self.summary())?;
+            Ok(())
+        }
+
+        /// The days reviewed so far along with the streak, shown after the session.
+        fn render_calendar(&self, frame: &mut Frame, style: &AppStyle) {
+            let streak = Streak::new(&self.calendar);
+            let block = Block::bordered()
+                .title(format!(
+                    "{} day streak, {} at the longest",
+                    streak.current, streak.longest
+                ))
+                .title_bottom(
+                    Line::styled(" c back to the summary · q back ", style.muted()).centered(),
+                );
+            let heatmap = Heatmap::new(&self.calendar, style).goal(self.daily_goal);
+            let area = center(
+                block.inner(frame.area()).inner(Margin::new(2, 1)),
+                Constraint::Max(CALENDAR_WIDTH),
+                Constraint::Length(8),
+            );
+            frame.render_widget(block, frame.area());
+            frame.render_widget(heatmap, area);
+        }
+
+        /// Lists the options of a multiple choice card,
+        /// once revealed the answer and a wrong pick are marked.
+        fn options(&self, style: &AppStyle) -> Vec<Line<'static>> {
+            let Some(current) = self.engine.current() else {
+                return vec![];
+            };
+            let back = current.item.back().to_string();
+            current
+                .item
+                .choices()
+                .iter()
+                .enumerate()
+                .map(|(i, option)| {
+                    let option = option.to_string();
+                    let text = format!("{}) {}", i + 1, option);
+                    if current.pinned {
+                        Line::from(text)
+                    } else if option == back {
+                        Line::styled(format!("{} ✓", text), style.string())
+                    } else if current.picked == Some(i) {
+                        Line::styled(format!("{} ✗", text), style.keyword())
+                    } else {
+                        Line::styled(text, style.muted())
+                    }
+                })
+                .collect()
+        }
+    }
+
+    impl<T, U, V> KadeuApp for FlashcardApp<T>
+    where
+        T: Kadeu<Front = U, Back = V> + From<crate::Flashcard>,
+        U: Display,
+        V: Display,
     {
         fn keymap(&self) -> KeyMap {
             let mut keymap = KeyMap::new();
             keymap.insert(KeyCode::Char('q'), Input::Escape);
             keymap.insert(KeyCode::Enter, Input::Continue);
+            keymap.insert(KeyCode::Char('y'), Input::Continue);
             keymap.insert(KeyCode::Esc, Input::Escape);
+            keymap.insert(KeyCode::Up, Input::Up);
+            keymap.insert(KeyCode::Char('k'), Input::Up);
+            keymap.insert(KeyCode::Down, Input::Down);
+            keymap.insert(KeyCode::Char('j'), Input::Down);
             keymap
         }
-        fn render<B: ratatui::prelude::Backend>(
-            &mut self,
-            terminal: &mut ratatui::Terminal<B>,
-            _: &AppStyle,
-        ) -> std::io::Result<()> {
+        fn render(&mut self, frame: &mut Frame, style: &AppStyle) {
             if self.show_title {
-                let text = Text::from(self.title.to_string());
+                let mut text = Text::from(self.title.to_string()).bold();
+                if let Some(notice) = self.skipped_notice() {
+                    text.push_line(Line::default());
+                    text.push_line(Line::styled(notice, style.muted()));
+                }
                 let block = Block::bordered();
-                terminal.draw(|frame| {
-                    let area = center(
-                        frame.area(),
-                        ratatui::layout::Constraint::Length(text.width() as u16),
-                        ratatui::layout::Constraint::Length(1),
-                    );
-                    frame.render_widget(block, frame.area());
-                    frame.render_widget(text, area);
-                })?;
-                return Ok(());
+                let area = block.inner(frame.area()).inner(Margin::new(2, 1));
+                frame.render_widget(block, frame.area());
+                render_scrollable(frame, area, text, 0);
+                return;
             }
 
             let Some(item) = self.engine.current() else {
-                // Draw End Splash
-                return Ok(());
+                if self.show_calendar {
+                    return self.render_calendar(frame, style);
+                }
+                let mut text = Text::from(vec![Line::from(self.summary()).bold()]);
+                match self.daily_goal {
+                    Some(goal) if self.reviewed_today() >= goal => {
+                        text.push_line(Line::styled(
+                            format!("Daily goal of {} reviews met", goal),
+                            style.string(),
+                        ));
+                    }
+                    Some(goal) => text.push_line(Line::styled(
+                        format!("{} of {} reviews for today", self.reviewed_today(), goal),
+                        style.muted(),
+                    )),
+                    None => {}
+                }
+                text.push_line(Line::default());
+                let keys = if self.calendar.is_empty() {
+                    "press q to go back"
+                } else {
+                    "c calendar · q back"
+                };
+                text.push_line(Line::styled(keys, style.muted()));
+                let block = Block::bordered().title(self.title.to_string());
+                let area = block.inner(frame.area()).inner(Margin::new(2, 1));
+                frame.render_widget(block, frame.area());
+                render_scrollable(frame, area, text, 0);
+                return;
             };
 
-            let mut text: Text = item.into();
-            text = text.centered();
-            let block = Block::bordered().title(self.title.to_string());
-            terminal.draw(|frame| {
-                let area = center(
-                    frame.area(),
-                    ratatui::layout::Constraint::Length(text.width() as u16),
-                    ratatui::layout::Constraint::Length(1),
-                );
-                frame.render_widget(block, frame.area());
-                frame.render_widget(text, area);
-            })?;
+            let (front, back) = item.faces();
+            let mut front = self.face(&front, style);
+            let options = self.options(style);
+            if !options.is_empty() {
+                front.push_line(Line::default());
+                front.extend(options);
+            }
+            if let Some(hint) = item.item.hint().filter(|_| item.hinted) {
+                front.push_line(Line::default());
+                front.push_line(Line::styled(format!("hint: {}", hint), style.muted()));
+            }
+            let mut back = back.map(|back| self.face(&back, style));
+            if let (Some(back), Some(notes)) = (back.as_mut(), item.item.notes()) {
+                if item.notes {
+                    back.push_line(Line::default());
+                    back.extend(self.face(notes, style).italic());
+                }
+            }
 
-            Ok(())
+            let mut keys = match (item.pinned, item.item.choices().is_empty()) {
+                (true, true) => vec!["enter reveal"],
+                (true, false) => vec!["1-9 pick", "enter give up"],
+                (false, true) => vec!["y/enter knew it", "n missed it"],
+                (false, false) => vec!["enter next"],
+            };
+            if item.pinned && !item.hinted && item.item.hint().is_some() {
+                keys.push("h hint");
+            }
+            if !item.pinned && item.item.notes().is_some() {
+                keys.push(if item.notes {
+                    "i hide notes"
+                } else {
+                    "i notes"
+                });
+            }
+            keys.extend(["e edit", "q back"]);
+            let keys = format!(" {} ", keys.join(" · "));
+            let title = match self.decks.get(&item.deck) {
+                Some(deck) if self.decks.len() > 1 => format!("{} · {}", self.title, deck),
+                _ => self.title.to_string(),
+            };
+            let mut block = Block::bordered()
+                .title(title)
+                .title_bottom(Line::styled(keys, style.muted()).centered());
+            if self.goal_met {
+                block =
+                    block.title(Line::styled(" daily goal met ", style.string()).right_aligned());
+            }
+            let area = center(
+                block.inner(frame.area()).inner(Margin::new(2, 1)),
+                Constraint::Max(MAX_TEXT_WIDTH),
+                Constraint::Percentage(100),
+            );
+            let mut text = front;
+            if let Some(back) = back {
+                let rule = "─".repeat(area.width as usize / 2);
+                text.push_line(Line::default());
+                text.push_line(Line::styled(rule, Style::new().dim()));
+                text.push_line(Line::default());
+                text.extend(back);
+            }
+            frame.render_widget(block, frame.area());
+            self.scroll = render_scrollable(frame, area, text, self.scroll);
         }
-        fn handle_input(&mut self, input: Option<&Input>) -> std::io::Result<Exit> {
+        fn handle_input(&mut self, input: Option<&Input>) -> std::io::Result<Intent> {
+            if let Some(Input::Up) = input {
+                self.scroll = self.scroll.saturating_sub(1);
+            }
+
+            if let Some(Input::Down) = input {
+                // clamped against the real text height on the next render
+                self.scroll = self.scroll.saturating_add(1);
+            }
+
+            if let Some(Input::Key(KeyCode::Char(c))) = input {
+                if let Some(n) = c.to_digit(10).filter(|n| *n > 0) {
+                    self.scroll = 0;
+                    self.pick(n as usize - 1);
+                }
+            }
+
             if let Some(Input::Continue) = input {
-                match self.engine.current() {
+                self.scroll = 0;
+                match self.engine.current.as_mut() {
                     Some(current) => {
                         if current.pinned {
-                            self.engine.unpin_current();
+                            // revealing a multiple choice card without picking gives it up
+                            if !current.item.choices().is_empty() {
+                                current.score = Some(Score::Miss);
+                            }
+                            current.unpin();
                         } else {
-                            self.engine.next();
+                            let score = current.score.unwrap_or(Score::Hit);
+                            self.record(score)?;
                         }
                     }
                     None => {
                         if self.show_title {
                             self.show_title = false;
+                            self.next();
                         }
-                        self.engine.next();
+                    }
+                }
+            }
 
-                        if self.engine.current().is_none() {
-                            self.show_end = true;
-                        }
+            if let Some(Input::Key(KeyCode::Char('n'))) = input {
+                let graded = self
+                    .engine
+                    .current()
+                    .is_some_and(|current| !current.pinned && current.item.choices().is_empty());
+                if graded {
+                    self.scroll = 0;
+                    self.record(Score::Miss)?;
+                }
+            }
+
+            if let Some(Input::Key(KeyCode::Char('h'))) = input {
+                if let Some(current) = self.engine.current.as_mut() {
+                    if current.pinned && current.item.hint().is_some() {
+                        current.hinted = true;
+                    }
+                }
+            }
+
+            if let Some(Input::Key(KeyCode::Char('i'))) = input {
+                if let Some(current) = self.engine.current.as_mut() {
+                    if !current.pinned {
+                        current.notes = !current.notes;
                     }
                 }
             }
 
+            if let Some(Input::Key(KeyCode::Char('e'))) = input {
+                if let Some(current) = self.engine.current() {
+                    return Ok(Intent::Push(Screen::Edit {
+                        path: PathBuf::from(&current.deck),
+                        focus: Some(current.item.front().to_string()),
+                    }));
+                }
+            }
+
+            if let Some(Input::Key(KeyCode::Char('c'))) = input {
+                if self.show_end && !self.calendar.is_empty() {
+                    self.show_calendar = !self.show_calendar;
+                }
+            }
+
             if let Some(Input::Escape) = input {
-                return Ok(Exit::Drop);
+                if self.show_calendar {
+                    self.show_calendar = false;
+                    return Ok(Intent::None);
+                }
+                return Ok(Intent::Pop);
             }
 
             if let Some(Input::Quit) = input {
-                return Ok(Exit::Quit);
+                return Ok(Intent::Quit);
             }
-            Ok(Exit::None)
+            Ok(Intent::None)
         }
+
+        /// Carries on with the card as it reads after it was edited.
+        fn resume(&mut self, outcome: Option<Outcome>) -> std::io::Result<Intent> {
+            if let Some(Outcome::Edited(card)) = outcome {
+                self.replace_current(T::from(card));
+            }
+            Ok(Intent::None)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::VecDeque;
+
+    use super::{strategies, ui::FlashcardApp, Engine};
+    use crate::game::{flashcard::Flashcard, schedule::Due, Direction, Score};
+
+    #[test]
+    fn interleave_takes_turns_between_decks() {
+        let items: VecDeque<Due<&str>> = ["a1", "a2", "a3", "b1", "c1", "c2"]
+            .into_iter()
+            .map(|card| Due {
+                deck: card[..1].to_string(),
+                card,
+                direction: Direction::Forward,
+            })
+            .collect();
+        let mut engine = Engine::new(items, strategies::interleave);
+        let mut order = vec![];
+        engine.next();
+        while let Some(card) = engine.current_mut() {
+            order.push(*card);
+            engine.next();
+        }
+        assert_eq!(order, vec!["a1", "b1", "c1", "a2", "c2", "a3"]);
+    }
+
+    #[test]
+    fn plain_sessions_grade_typed_answers() {
+        let items = [("hund", "dog"), ("katze", "cat")]
+            .into_iter()
+            .map(|(front, back)| Due {
+                deck: "german".to_string(),
+                card: Flashcard::new(front.to_string(), back.to_string()),
+                direction: Direction::Forward,
+            })
+            .collect();
+        let mut app = FlashcardApp::new("german", false, items);
+        // a matching answer is taken as right, a wrong one has to be overruled
+        let input = "Dog\n\nmouse\n\n";
+        let mut output = vec![];
+        app.run_plain(input.as_bytes(), &mut output).unwrap();
+        assert_eq!(app.results(), &[Score::Hit, Score::Miss]);
+        assert!(String::from_utf8(output)
+            .unwrap()
+            .ends_with("End of deck, 1 of 2 right\n"));
     }
 }
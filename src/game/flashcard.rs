@@ -2,12 +2,84 @@ use std::fmt::Display;
 
 use serde::{Deserialize, Serialize};
 
-use super::Kadeu;
+use super::{fingerprint, Expand, Kadeu};
 
-#[derive(Deserialize, Serialize)]
+#[derive(Deserialize, Serialize, Clone, Debug)]
 pub struct Flashcard<T, U> {
+    /// Keeps the card's review history when its front is reworded.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    id: Option<String>,
     front: T,
     back: U,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    reversible: Option<bool>,
+    /// Options to pick the back from, the card is answered by typing instead when empty.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    choices: Vec<U>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    hint: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    notes: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    tags: Vec<String>,
+}
+
+impl<T, U> Flashcard<T, U> {
+    pub fn new(front: T, back: U) -> Self {
+        Self {
+            id: None,
+            front,
+            back,
+            reversible: None,
+            choices: vec![],
+            hint: None,
+            notes: None,
+            tags: vec![],
+        }
+    }
+
+    pub fn with_id(mut self, id: String) -> Self {
+        self.id = Some(id);
+        self
+    }
+
+    pub fn set_reversible(&mut self, reversible: Option<bool>) {
+        self.reversible = reversible;
+    }
+
+    pub fn with_choices(mut self, choices: Vec<U>) -> Self {
+        self.choices = choices;
+        self
+    }
+
+    pub fn with_tags(mut self, tags: Vec<String>) -> Self {
+        self.tags = tags;
+        self
+    }
+
+    pub fn set_tags(&mut self, tags: Vec<String>) {
+        self.tags = tags;
+    }
+
+    pub fn choices_mut(&mut self) -> &mut Vec<U> {
+        &mut self.choices
+    }
+
+    pub fn set_hint(&mut self, hint: Option<String>) {
+        self.hint = hint;
+    }
+
+    pub fn set_notes(&mut self, notes: Option<String>) {
+        self.notes = notes;
+    }
+
+    pub fn set_front(&mut self, front: T) {
+        self.front = front;
+    }
+
+    pub fn set_back(&mut self, back: U) {
+        self.back = back;
+    }
 }
 
 impl<T, U> Kadeu for Flashcard<T, U>
@@ -25,6 +97,41 @@ where
     fn back(&self) -> &Self::Back {
         &self.back
     }
+
+    fn choices(&self) -> &[Self::Back] {
+        &self.choices
+    }
+
+    fn reversible(&self) -> Option<bool> {
+        self.reversible
+    }
+
+    fn hint(&self) -> Option<&str> {
+        self.hint.as_deref()
+    }
+
+    fn notes(&self) -> Option<&str> {
+        self.notes.as_deref()
+    }
+
+    fn tags(&self) -> &[String] {
+        &self.tags
+    }
+
+    fn id(&self) -> String {
+        match &self.id {
+            Some(id) => id.clone(),
+            None => fingerprint(&self.front.to_string()),
+        }
+    }
+}
+
+impl<T: Clone, U: Clone> Expand for Flashcard<T, U> {
+    type Item = Self;
+
+    fn expand(&self) -> Vec<Self::Item> {
+        vec![self.clone()]
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
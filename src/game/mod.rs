@@ -1,12 +1,103 @@
 use std::fmt::Display;
 
+use serde::{Deserialize, Serialize};
+
+pub mod choice;
+pub mod cloze;
 pub mod engine;
 pub mod flashcard;
+pub mod note;
+pub mod query;
+pub mod review;
+pub mod schedule;
+pub mod stats;
+pub mod tags;
 pub trait Kadeu {
     type Front;
     type Back;
     fn front(&self) -> &Self::Front;
     fn back(&self) -> &Self::Back;
+    /// Options for multiple choice cards, one of them is the back.
+    fn choices(&self) -> &[Self::Back] {
+        &[]
+    }
+    /// Some(true) if the card is always studied both ways round,
+    /// Some(false) if it only makes sense front first, None to leave it to the session.
+    fn reversible(&self) -> Option<bool> {
+        None
+    }
+    /// A nudge towards the back which can be asked for before the reveal.
+    fn hint(&self) -> Option<&str> {
+        None
+    }
+    /// Longer explanations which can be opened once the back is revealed.
+    fn notes(&self) -> Option<&str> {
+        None
+    }
+    /// Labels to pick subsets of a deck by, the deck's own tags aren't included.
+    fn tags(&self) -> &[String] {
+        &[]
+    }
+    /// Identifies the card within its deck in the review log.
+    fn id(&self) -> String
+    where
+        Self::Front: Display,
+    {
+        fingerprint(&self.front().to_string())
+    }
+}
+
+/// A stable hash of some text, used to identify cards which don't carry an id.
+pub fn fingerprint(text: &str) -> String {
+    // FNV-1a, std's hasher isn't guaranteed to be stable between releases.
+    let hash = text.bytes().fold(0xcbf29ce484222325u64, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    });
+    format!("{:016x}", hash)
+}
+
+/// Which way round a card is shown.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum Direction {
+    /// Front first.
+    #[default]
+    Forward,
+    /// Back first.
+    Backward,
+}
+
+/// Which directions the cards of a session are studied in.
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Default)]
+pub enum DirectionMode {
+    /// Front first, plus back first for cards which declare themselves reversible.
+    #[default]
+    Forward,
+    /// Back first.
+    Backward,
+    /// Both ways round, each scheduled on its own.
+    Both,
+}
+
+impl DirectionMode {
+    /// The directions a card is studied in during the session.
+    pub fn directions(&self, reversible: Option<bool>) -> Vec<Direction> {
+        match (reversible, self) {
+            (Some(false), _) => vec![Direction::Forward],
+            (Some(true), Self::Forward) | (_, Self::Both) => {
+                vec![Direction::Forward, Direction::Backward]
+            }
+            (None, Self::Forward) => vec![Direction::Forward],
+            (_, Self::Backward) => vec![Direction::Backward],
+        }
+    }
+}
+
+/// Entries of a deck which turn into one or more reviewable cards,
+/// e.g. a cloze sentence produces a card per deletion.
+pub trait Expand {
+    type Item;
+    fn expand(&self) -> Vec<Self::Item>;
 }
 
 impl<T, U> Kadeu for (T, U)
@@ -25,8 +116,12 @@ where
     }
 }
 
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
 pub enum Score {
     Hit,
+    /// Remembered, but only with help.
+    Hard,
     Miss,
 }
 
@@ -34,26 +129,50 @@ impl Score {
     pub fn to_string(&self) -> String {
         String::from(match self {
             Self::Hit => "hit",
+            Self::Hard => "hard",
             Self::Miss => "miss",
         })
     }
+
+    fn rank(&self) -> u8 {
+        match self {
+            Self::Miss => 0,
+            Self::Hard => 1,
+            Self::Hit => 2,
+        }
+    }
+
+    /// The score, lowered to `max` if it is any better.
+    pub fn cap(self, max: Score) -> Score {
+        if self.rank() > max.rank() {
+            max
+        } else {
+            self
+        }
+    }
 }
 
+/// An item along with the score it got last, if it has been scored yet.
+#[derive(Debug, Clone, PartialEq)]
 pub struct Progress<T> {
     item: T,
     score: Option<Score>,
 }
 
 impl<T> Progress<T> {
+    pub fn new(item: T) -> Self {
+        Self { item, score: None }
+    }
+
+    pub fn item(&self) -> &T {
+        &self.item
+    }
+
     pub fn set_score(&mut self, score: Score) {
         self.score = Some(score)
     }
 
-    fn score(&self) -> Option<&Score> {
-        if let Some(score) = &self.score {
-            Some(score)
-        } else {
-            None
-        }
+    pub fn score(&self) -> Option<&Score> {
+        self.score.as_ref()
     }
 }
@@ -0,0 +1,269 @@
+use std::fmt;
+
+use serde::{
+    de::{MapAccess, Visitor},
+    ser::SerializeMap,
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+
+use super::{fingerprint, flashcard::Flashcard, Expand};
+
+const OPEN: &str = "{{";
+const CLOSE: &str = "}}";
+
+/// Named fields of one entry, such as a word with its reading and meaning.
+/// The deck's templates decide which cards are made from them.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Note {
+    /// Keeps the history of the note's cards when its fields are reworded.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    id: Option<String>,
+    note: Fields,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    tags: Vec<String>,
+}
+
+/// Fields in the order they were written, kept as a map in the deck file.
+#[derive(Clone, Debug, Default, PartialEq)]
+struct Fields(Vec<(String, String)>);
+
+/// Lays the fields of a note out on a card, `{{word}}` is replaced by the `word` field.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Template {
+    front: String,
+    back: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    reversible: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    hint: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    notes: Option<String>,
+}
+
+impl Note {
+    /// A note with every one of the fields empty.
+    pub fn new(fields: &[String]) -> Self {
+        Self {
+            id: None,
+            note: Fields(
+                fields
+                    .iter()
+                    .map(|name| (name.clone(), String::new()))
+                    .collect(),
+            ),
+            tags: vec![],
+        }
+    }
+
+    pub fn fields(&self) -> &[(String, String)] {
+        &self.note.0
+    }
+
+    pub fn field(&self, name: &str) -> Option<&str> {
+        self.note
+            .0
+            .iter()
+            .find(|(field, _)| field == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    pub fn set_field(&mut self, index: usize, value: String) {
+        if let Some((_, old)) = self.note.0.get_mut(index) {
+            *old = value;
+        }
+    }
+
+    pub fn tags(&self) -> &[String] {
+        &self.tags
+    }
+
+    /// Identifies the note, by every one of its fields unless it carries an id.
+    /// Its cards add the index of their template to it.
+    pub fn id(&self) -> String {
+        if let Some(id) = &self.id {
+            return id.clone();
+        }
+        let fields: Vec<String> = self
+            .note
+            .0
+            .iter()
+            .map(|(name, value)| format!("{}\0{}", name, value))
+            .collect();
+        fingerprint(&fields.join("\n"))
+    }
+
+    pub fn set_tags(&mut self, tags: Vec<String>) {
+        self.tags = tags;
+    }
+
+    /// The cards of the note, one per template which has something to show on the front.
+    pub fn cards(&self, templates: &[Template]) -> Vec<Flashcard<String, String>> {
+        let id = self.id();
+        templates
+            .iter()
+            .enumerate()
+            .filter_map(|(i, template)| {
+                let card = template.render(self)?;
+                Some(card.with_id(format!("{}-{}", id, i)))
+            })
+            .map(|card| card.with_tags(self.tags.clone()))
+            .collect()
+    }
+}
+
+impl Template {
+    pub fn new(front: &str, back: &str) -> Self {
+        Self {
+            front: front.to_string(),
+            back: back.to_string(),
+            reversible: None,
+            hint: None,
+            notes: None,
+        }
+    }
+
+    /// The names of the fields the template refers to.
+    pub fn fields(&self) -> Vec<&str> {
+        let mut names = vec![];
+        let texts = [
+            Some(&self.front),
+            Some(&self.back),
+            self.hint.as_ref(),
+            self.notes.as_ref(),
+        ];
+        for text in texts.into_iter().flatten() {
+            let mut rest = text.as_str();
+            while let Some((name, after)) = placeholder(rest) {
+                names.push(name);
+                rest = after;
+            }
+        }
+        names
+    }
+
+    /// Fills the template in, None if the front comes out empty.
+    pub fn render(&self, note: &Note) -> Option<Flashcard<String, String>> {
+        let front = fill(&self.front, note);
+        if front.trim().is_empty() {
+            return None;
+        }
+        let mut card = Flashcard::new(front, fill(&self.back, note));
+        card.set_reversible(self.reversible);
+        // a hint or notes with none of their fields filled in are left off
+        let optional = |template: &Option<String>| {
+            template
+                .as_ref()
+                .map(|template| fill(template, note))
+                .filter(|text| !text.trim().is_empty())
+        };
+        card.set_hint(optional(&self.hint));
+        card.set_notes(optional(&self.notes));
+        Some(card)
+    }
+}
+
+/// Without templates a note shows its first field and puts the rest on the back.
+impl Expand for Note {
+    type Item = Flashcard<String, String>;
+
+    fn expand(&self) -> Vec<Self::Item> {
+        let mut fields = self.note.0.iter().map(|(_, value)| value.as_str());
+        let Some(front) = fields.next().filter(|front| !front.trim().is_empty()) else {
+            return vec![];
+        };
+        let back: Vec<&str> = fields.filter(|value| !value.trim().is_empty()).collect();
+        let card = Flashcard::new(front.to_string(), back.join("\n")).with_id(self.id());
+        vec![card.with_tags(self.tags.clone())]
+    }
+}
+
+/// The first `{{name}}` in the text and the text after it.
+fn placeholder(text: &str) -> Option<(&str, &str)> {
+    let start = text.find(OPEN)? + OPEN.len();
+    let end = text[start..].find(CLOSE)? + start;
+    Some((text[start..end].trim(), &text[end + CLOSE.len()..]))
+}
+
+/// Replaces the placeholders, fields the note doesn't have come out empty.
+fn fill(template: &str, note: &Note) -> String {
+    let mut filled = String::new();
+    let mut rest = template;
+    while let Some((name, after)) = placeholder(rest) {
+        let start = rest.find(OPEN).unwrap_or_default();
+        filled.push_str(&rest[..start]);
+        filled.push_str(note.field(name).unwrap_or_default());
+        rest = after;
+    }
+    filled.push_str(rest);
+    filled
+}
+
+impl Serialize for Fields {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(self.0.len()))?;
+        for (name, value) in &self.0 {
+            map.serialize_entry(name, value)?;
+        }
+        map.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for Fields {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct FieldsVisitor;
+
+        impl<'de> Visitor<'de> for FieldsVisitor {
+            type Value = Fields;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a map of field names to text")
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Fields, A::Error> {
+                let mut fields = vec![];
+                while let Some(entry) = map.next_entry()? {
+                    fields.push(entry);
+                }
+                Ok(Fields(fields))
+            }
+        }
+
+        deserializer.deserialize_map(FieldsVisitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Note, Template};
+    use crate::game::Kadeu;
+
+    #[test]
+    fn templates_render_fields() {
+        let note: Note = serde_json::from_str(
+            r#"{"note": {"word": "犬", "reading": "いぬ", "meaning": "dog"}}"#,
+        )
+        .unwrap();
+        let templates = vec![
+            Template::new("{{word}}", "{{reading}}\n{{meaning}}"),
+            Template::new("{{meaning}}", "{{word}}"),
+            Template::new("{{example}}", "{{word}}"),
+        ];
+        let cards = note.cards(&templates);
+        assert_eq!(cards.len(), 2);
+        assert_eq!(cards[0].front(), "犬");
+        assert_eq!(cards[0].back(), "いぬ\ndog");
+        assert_eq!(cards[1].front(), "dog");
+
+        // cards with the same front keep apart, by template and by note
+        let same = vec![Template::new("{{word}}", "{{reading}}"); 2];
+        let cards = note.cards(&same);
+        assert_ne!(cards[0].id(), cards[1].id());
+        let other: Note =
+            serde_json::from_str(r#"{"note": {"word": "犬", "meaning": "dog"}}"#).unwrap();
+        assert_ne!(other.cards(&same)[0].id(), cards[0].id());
+        assert_eq!(
+            serde_json::to_string(&note).unwrap(),
+            r#"{"note":{"word":"犬","reading":"いぬ","meaning":"dog"}}"#
+        );
+    }
+}
@@ -0,0 +1,229 @@
+use chrono::{DateTime, Duration, Local, NaiveDate};
+
+use super::schedule::State;
+use crate::error::{Error, Result};
+
+/// Extension of the filtered decks saved by `kadeu study --save`.
+pub const QUERY_EXTENSION: &str = "query";
+
+/// Picks cards from any number of decks, written as space separated terms which all have to match.
+///
+/// - `deck:networking/*` the path of the deck under the import directory, `*` and `?` wildcards
+/// - `tag:tcp` a tag of the card or its deck
+/// - `is:due`, `is:new` cards that are due or have never been reviewed
+/// - `misses:3` cards missed at least 3 times
+/// - `reviewed:7`, `missed:7` reviewed or missed in the last 7 days, or since a date like `2024-05-01`
+/// - anything else is looked for in the text of the card, use quotes to keep words together
+///
+/// A term starting with `-` picks the cards which don't match it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Query {
+    terms: Vec<(bool, Term)>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Term {
+    Deck(String),
+    Tag(String),
+    Due,
+    New,
+    Misses(usize),
+    Reviewed(Since),
+    Missed(Since),
+    Text(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Since {
+    Days(i64),
+    Date(NaiveDate),
+}
+
+/// What a query gets to look at for one direction of a card.
+pub struct Candidate<'a> {
+    /// Path of the deck relative to the import directory, with `/` between the parts.
+    pub deck: &'a str,
+    pub tags: Vec<&'a String>,
+    pub text: String,
+    pub state: Option<&'a State>,
+}
+
+impl Query {
+    pub fn parse(query: &str) -> Result<Self> {
+        let mut terms = vec![];
+        for word in words(query) {
+            let (negated, word) = match word.strip_prefix('-') {
+                Some(rest) if !rest.is_empty() => (true, rest.to_string()),
+                _ => (false, word),
+            };
+            terms.push((negated, Term::parse(&word)?));
+        }
+        Ok(Self { terms })
+    }
+
+    pub fn matches(&self, candidate: &Candidate, now: DateTime<Local>) -> bool {
+        self.terms
+            .iter()
+            .all(|(negated, term)| term.matches(candidate, now) != *negated)
+    }
+}
+
+impl Term {
+    fn parse(word: &str) -> Result<Self> {
+        let invalid = |message: &str| Error::Query {
+            term: word.to_string(),
+            message: message.to_string(),
+        };
+        let Some((key, value)) = word.split_once(':') else {
+            return Ok(Self::Text(word.to_lowercase()));
+        };
+        let since = || Since::parse(value).ok_or_else(|| invalid("expected days or a date"));
+        let term = match key {
+            "deck" => Self::Deck(value.to_string()),
+            "tag" => Self::Tag(value.to_lowercase()),
+            "is" => match value {
+                "due" => Self::Due,
+                "new" => Self::New,
+                _ => return Err(invalid("expected is:due or is:new")),
+            },
+            "misses" => Self::Misses(value.parse().map_err(|_| invalid("expected a number"))?),
+            "reviewed" => Self::Reviewed(since()?),
+            "missed" => Self::Missed(since()?),
+            // a colon in ordinary text, such as a time
+            _ => Self::Text(word.to_lowercase()),
+        };
+        Ok(term)
+    }
+
+    fn matches(&self, candidate: &Candidate, now: DateTime<Local>) -> bool {
+        let state = candidate.state;
+        match self {
+            Self::Deck(pattern) => glob(pattern, candidate.deck),
+            Self::Tag(tag) => candidate
+                .tags
+                .iter()
+                .any(|other| other.to_lowercase() == *tag),
+            Self::Due => state.is_none_or(|state| state.due <= now),
+            Self::New => state.is_none(),
+            Self::Misses(misses) => state.is_some_and(|state| state.misses >= *misses),
+            Self::Reviewed(since) => {
+                state.is_some_and(|state| state.last_reviewed >= since.start(now))
+            }
+            Self::Missed(since) => state
+                .and_then(|state| state.last_missed)
+                .is_some_and(|missed| missed >= since.start(now)),
+            Self::Text(text) => candidate.text.to_lowercase().contains(text),
+        }
+    }
+}
+
+impl Since {
+    /// `7`, `7d` or `2024-05-01`.
+    fn parse(value: &str) -> Option<Self> {
+        let days = value.strip_suffix('d').unwrap_or(value);
+        if let Ok(days) = days.parse() {
+            return Some(Self::Days(days));
+        }
+        NaiveDate::parse_from_str(value, "%Y-%m-%d")
+            .ok()
+            .map(Self::Date)
+    }
+
+    fn start(&self, now: DateTime<Local>) -> DateTime<Local> {
+        match self {
+            Self::Days(days) => now - Duration::days(*days),
+            Self::Date(date) => date
+                .and_hms_opt(0, 0, 0)
+                .and_then(|start| start.and_local_timezone(Local).earliest())
+                .unwrap_or(now),
+        }
+    }
+}
+
+/// Splits the query on whitespace, keeping "quoted words" together.
+fn words(query: &str) -> Vec<String> {
+    let mut words = vec![];
+    let mut word = String::new();
+    let mut quoted = false;
+    for c in query.chars() {
+        match c {
+            '"' => quoted = !quoted,
+            c if c.is_whitespace() && !quoted => {
+                if !word.is_empty() {
+                    words.push(std::mem::take(&mut word));
+                }
+            }
+            c => word.push(c),
+        }
+    }
+    if !word.is_empty() {
+        words.push(word);
+    }
+    words
+}
+
+/// Matches a path against a pattern where `*` stands for any run of characters
+/// within one part of the path, `**` for any number of parts and `?` for one character.
+pub(crate) fn glob(pattern: &str, path: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let path: Vec<char> = path.chars().collect();
+    glob_from(&pattern, &path)
+}
+
+fn glob_from(pattern: &[char], path: &[char]) -> bool {
+    match pattern {
+        [] => path.is_empty(),
+        ['*', '*', rest @ ..] => (0..=path.len()).any(|i| glob_from(rest, &path[i..])),
+        ['*', rest @ ..] => (0..=path.len())
+            .take_while(|i| *i == 0 || path[i - 1] != '/')
+            .any(|i| glob_from(rest, &path[i..])),
+        ['?', rest @ ..] => matches!(path, [c, ..] if *c != '/') && glob_from(rest, &path[1..]),
+        [c, rest @ ..] => path.first() == Some(c) && glob_from(rest, &path[1..]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, Local};
+
+    use super::{glob, Candidate, Query};
+    use crate::game::schedule::State;
+
+    #[test]
+    fn globs_stay_within_a_directory() {
+        assert!(glob("networking/*", "networking/tcp.json"));
+        assert!(!glob("networking/*", "networking/ip/v6.json"));
+        assert!(glob("networking/**", "networking/ip/v6.json"));
+        assert!(glob("*/t?p.*", "networking/tcp.json"));
+    }
+
+    #[test]
+    fn terms_all_have_to_match() {
+        let now = Local::now();
+        let tag = "tcp".to_string();
+        let state = State {
+            interval: 0,
+            due: now,
+            first_reviewed: now - Duration::days(9),
+            last_reviewed: now - Duration::days(2),
+            last_missed: Some(now - Duration::days(2)),
+            reviews: 3,
+            misses: 2,
+        };
+        let candidate = Candidate {
+            deck: "networking/tcp.json",
+            tags: vec![&tag],
+            text: "What does the SYN flag do?".to_string(),
+            state: Some(&state),
+        };
+        let query =
+            Query::parse(r#"deck:networking/* tag:TCP missed:7 "syn flag" -is:new"#).unwrap();
+        assert!(query.matches(&candidate, now));
+        assert!(!Query::parse("misses:3").unwrap().matches(&candidate, now));
+        assert!(!Query::parse("missed:1").unwrap().matches(&candidate, now));
+        assert_eq!(
+            Query::parse("is:unknown").unwrap_err().to_string(),
+            "`is:unknown` in the query: expected is:due or is:new"
+        );
+    }
+}
@@ -0,0 +1,188 @@
+use std::{
+    fs::{self, OpenOptions},
+    io::{BufRead, BufReader, Write},
+    path::{Path, PathBuf},
+};
+
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+
+use super::{Direction, Score};
+use crate::error::{Error, Result};
+
+/// One answer given to a card, as kept in the review log.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Review {
+    /// Path of the deck the card was studied from.
+    pub deck: String,
+    /// Id of the card within the deck, see `Kadeu::id`.
+    pub card: String,
+    #[serde(default)]
+    pub direction: Direction,
+    pub score: Score,
+    /// The hint was shown before the card was answered.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub hinted: bool,
+    /// How long the card was on display before it was revealed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub answer_ms: Option<u64>,
+    pub reviewed_at: DateTime<Local>,
+}
+
+/// A review along with what is known of its deck and card, one row of an export.
+/// Cards which are no longer in their deck are left blank.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct Record {
+    pub reviewed_at: DateTime<Local>,
+    pub deck: String,
+    pub deck_title: String,
+    pub card: String,
+    pub front: String,
+    pub back: String,
+    /// The tags of the card and its deck, separated by spaces.
+    pub tags: String,
+    pub direction: Direction,
+    pub score: Score,
+    pub hinted: bool,
+    pub answer_ms: Option<u64>,
+}
+
+/// What could be read of a review log.
+#[derive(Debug, Default)]
+pub struct Loaded {
+    pub reviews: Vec<Review>,
+    /// Why each line that was left out couldn't be read, e.g. one a crash cut short.
+    pub skipped: Vec<Error>,
+}
+
+/// Append only log of every review, one json object per line.
+#[derive(Debug, Clone)]
+pub struct ReviewLog {
+    path: PathBuf,
+}
+
+impl ReviewLog {
+    pub fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn append(&self, review: &Review) -> std::io::Result<()> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        let line = serde_json::to_string(review)?;
+        writeln!(file, "{}", line)
+    }
+
+    /// Replaces every review in the log, e.g. once decks have been moved around.
+    pub fn rewrite(&self, reviews: &[Review]) -> std::io::Result<()> {
+        let mut buf = String::new();
+        for review in reviews {
+            buf.push_str(&serde_json::to_string(review)?);
+            buf.push('\n');
+        }
+        // written aside first so a failure doesn't cost the history
+        let temporary = self.path.with_extension("tmp");
+        fs::write(&temporary, buf)?;
+        fs::rename(temporary, &self.path)
+    }
+
+    /// Moves reviews over to the deck `to` gives them, leaving the rest where they are.
+    /// Returns how many reviews were moved.
+    pub fn relocate(&self, to: impl Fn(&Review) -> Option<String>) -> std::io::Result<usize> {
+        let mut reviews = self.load()?;
+        let mut moved = 0;
+        for review in reviews.iter_mut() {
+            match to(review) {
+                Some(deck) if deck != review.deck => {
+                    review.deck = deck;
+                    moved += 1;
+                }
+                _ => {}
+            }
+        }
+        if moved > 0 {
+            self.rewrite(&reviews)?;
+        }
+        Ok(moved)
+    }
+
+    /// Every review in the order they were given, a missing log has no reviews yet.
+    /// Lines which can't be read are skipped, see `read`.
+    pub fn load(&self) -> Result<Vec<Review>> {
+        Ok(self.read()?.reviews)
+    }
+
+    /// Every review that can be read along with the lines that couldn't,
+    /// only failing when the log itself can't be read.
+    pub fn read(&self) -> Result<Loaded> {
+        let mut loaded = Loaded::default();
+        if !self.path.exists() {
+            return Ok(loaded);
+        }
+        let file = OpenOptions::new()
+            .read(true)
+            .open(&self.path)
+            .map_err(Error::io(&self.path))?;
+        for (i, line) in BufReader::new(file).lines().enumerate() {
+            let line = line.map_err(Error::io(&self.path))?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            match serde_json::from_str(&line) {
+                Ok(review) => loaded.reviews.push(review),
+                Err(e) => {
+                    let position = Some((i + 1, e.column()));
+                    loaded.skipped.push(Error::parse(&self.path, position, &e));
+                }
+            }
+        }
+        Ok(loaded)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use chrono::Local;
+
+    use super::{Review, ReviewLog};
+    use crate::game::{Direction, Score};
+
+    #[test]
+    fn unreadable_lines_are_skipped() {
+        let path = std::env::temp_dir().join(format!("kadeu-reviews-{}.jsonl", std::process::id()));
+        let log = ReviewLog::new(path.clone());
+        let review = |card: &str| Review {
+            deck: "german".to_string(),
+            card: card.to_string(),
+            direction: Direction::Forward,
+            score: Score::Hit,
+            hinted: false,
+            answer_ms: None,
+            reviewed_at: Local::now(),
+        };
+        log.append(&review("eins")).unwrap();
+        // what a crash halfway through a write leaves behind
+        let mut text = fs::read_to_string(&path).unwrap();
+        text.push_str("{\"deck\": \"german\", \"ca\n");
+        fs::write(&path, text).unwrap();
+        log.append(&review("zwei")).unwrap();
+
+        let loaded = log.read().unwrap();
+        let cards: Vec<&str> = loaded.reviews.iter().map(|r| r.card.as_str()).collect();
+        assert_eq!(cards, vec!["eins", "zwei"]);
+        assert_eq!(loaded.skipped.len(), 1);
+        assert!(loaded.skipped[0].to_string().contains(".jsonl:2:"));
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,379 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Duration, Local};
+use serde::Serialize;
+
+use super::{review::Review, Direction, DirectionMode, Kadeu, Score};
+use std::fmt::Display;
+
+/// Identifies one direction of one card of a deck.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Key {
+    pub deck: String,
+    pub card: String,
+    pub direction: Direction,
+}
+
+impl From<&Review> for Key {
+    fn from(review: &Review) -> Self {
+        Self {
+            deck: review.deck.clone(),
+            card: review.card.clone(),
+            direction: review.direction,
+        }
+    }
+}
+
+/// A card picked for a session, the way round it is shown and the deck it came from.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Due<T> {
+    pub deck: String,
+    pub card: T,
+    pub direction: Direction,
+}
+
+/// How many cards a deck takes in a day, None for no limit.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Limits {
+    /// Cards seen for the first time.
+    pub new: Option<usize>,
+    /// Cards coming back after being remembered.
+    pub reviews: Option<usize>,
+}
+
+/// What is waiting in a session, see `Schedule::counts`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct Counts {
+    /// Never reviewed.
+    pub new: usize,
+    /// Missed last time and being learned again.
+    pub learning: usize,
+    /// Remembered last time and now due again.
+    pub due: usize,
+}
+
+impl<T> Due<T>
+where
+    T: Kadeu,
+    T::Front: Display,
+{
+    pub fn key(&self) -> Key {
+        Key {
+            deck: self.deck.clone(),
+            card: self.card.id(),
+            direction: self.direction,
+        }
+    }
+}
+
+/// Where a card stands after its reviews so far.
+#[derive(Debug, Clone, PartialEq)]
+pub struct State {
+    /// Days until the card comes up again, 0 while it is being relearned.
+    pub interval: i64,
+    pub due: DateTime<Local>,
+    pub first_reviewed: DateTime<Local>,
+    pub last_reviewed: DateTime<Local>,
+    pub last_missed: Option<DateTime<Local>>,
+    pub reviews: usize,
+    pub misses: usize,
+}
+
+/// Spaces out cards by doubling the interval every time a card is remembered
+/// and starting over when it is missed.
+#[derive(Debug, Clone, Default)]
+pub struct Schedule {
+    states: HashMap<Key, State>,
+}
+
+impl Schedule {
+    pub fn from_reviews(reviews: &[Review]) -> Self {
+        let mut schedule = Self::default();
+        for review in reviews {
+            schedule.record(review);
+        }
+        schedule
+    }
+
+    pub fn record(&mut self, review: &Review) {
+        let state = self.states.entry(Key::from(review)).or_insert(State {
+            interval: 0,
+            due: review.reviewed_at,
+            first_reviewed: review.reviewed_at,
+            last_reviewed: review.reviewed_at,
+            last_missed: None,
+            reviews: 0,
+            misses: 0,
+        });
+        state.interval = match review.score {
+            Score::Hit if state.interval == 0 => 1,
+            Score::Hit => state.interval * 2,
+            // remembered with help, try again after the same wait
+            Score::Hard => state.interval.max(1),
+            Score::Miss => 0,
+        };
+        if review.score == Score::Miss {
+            state.misses += 1;
+            state.last_missed = Some(review.reviewed_at);
+        }
+        state.reviews += 1;
+        state.last_reviewed = review.reviewed_at;
+        state.due = review.reviewed_at + Duration::days(state.interval);
+    }
+
+    pub fn state(&self, key: &Key) -> Option<&State> {
+        self.states.get(key)
+    }
+
+    /// Cards which have never been reviewed are always due.
+    pub fn is_due(&self, key: &Key, now: DateTime<Local>) -> bool {
+        match self.state(key) {
+            Some(state) => state.due <= now,
+            None => true,
+        }
+    }
+
+    /// Picks the directions of the cards that are up for review in the session.
+    /// With `all` set every direction is studied regardless of when it is due.
+    pub fn due<T>(
+        &self,
+        deck: &str,
+        cards: Vec<T>,
+        mode: DirectionMode,
+        all: bool,
+        now: DateTime<Local>,
+    ) -> Vec<Due<T>>
+    where
+        T: Kadeu + Clone,
+        T::Front: Display,
+    {
+        self.select(deck, cards, mode, |_, key| all || self.is_due(key, now))
+    }
+
+    /// New cards and reviews already studied today from the deck, or from every deck for None.
+    /// The reviews are counted as due.
+    pub fn studied_today(&self, deck: Option<&str>, now: DateTime<Local>) -> Counts {
+        let today = now.date_naive();
+        let mut counts = Counts::default();
+        for (key, state) in &self.states {
+            if deck.is_some_and(|deck| key.deck != deck)
+                || state.last_reviewed.date_naive() != today
+            {
+                continue;
+            }
+            if state.first_reviewed.date_naive() == today {
+                counts.new += 1;
+            } else {
+                counts.due += 1;
+            }
+        }
+        counts
+    }
+
+    /// Holds back the new cards and reviews over what is left of the day's limits.
+    /// Cards being learned again after a miss are never held back.
+    pub fn limit<T>(&self, items: Vec<Due<T>>, limits: Limits, now: DateTime<Local>) -> Vec<Due<T>>
+    where
+        T: Kadeu,
+        T::Front: Display,
+    {
+        self.hold_back(items, limits, now, |item| Some(item.deck.clone()))
+    }
+
+    /// Like `limit` with the limits shared between the cards of every deck,
+    /// counting what was studied today from any of them.
+    pub fn limit_total<T>(
+        &self,
+        items: Vec<Due<T>>,
+        limits: Limits,
+        now: DateTime<Local>,
+    ) -> Vec<Due<T>>
+    where
+        T: Kadeu,
+        T::Front: Display,
+    {
+        self.hold_back(items, limits, now, |_| None)
+    }
+
+    /// Applies the limits to the items of each group, None being every deck at once.
+    fn hold_back<T>(
+        &self,
+        items: Vec<Due<T>>,
+        limits: Limits,
+        now: DateTime<Local>,
+        group: impl Fn(&Due<T>) -> Option<String>,
+    ) -> Vec<Due<T>>
+    where
+        T: Kadeu,
+        T::Front: Display,
+    {
+        let mut left: HashMap<Option<String>, (Option<usize>, Option<usize>)> = HashMap::new();
+        let mut kept = vec![];
+        for item in items {
+            let (new, reviews) = left.entry(group(&item)).or_insert_with_key(|deck| {
+                let studied = self.studied_today(deck.as_deref(), now);
+                (
+                    limits.new.map(|limit| limit.saturating_sub(studied.new)),
+                    limits
+                        .reviews
+                        .map(|limit| limit.saturating_sub(studied.due)),
+                )
+            });
+            let left = match self.state(&item.key()) {
+                None => new,
+                Some(state) if state.interval > 0 => reviews,
+                Some(_) => {
+                    kept.push(item);
+                    continue;
+                }
+            };
+            match left {
+                Some(0) => {}
+                Some(n) => {
+                    *n -= 1;
+                    kept.push(item);
+                }
+                None => kept.push(item),
+            }
+        }
+        kept
+    }
+
+    /// Sorts the cards of a session into new, learning and due.
+    pub fn counts<T>(&self, items: &[Due<T>]) -> Counts
+    where
+        T: Kadeu,
+        T::Front: Display,
+    {
+        let mut counts = Counts::default();
+        for item in items {
+            match self.state(&item.key()) {
+                None => counts.new += 1,
+                Some(state) if state.interval == 0 => counts.learning += 1,
+                Some(_) => counts.due += 1,
+            }
+        }
+        counts
+    }
+
+    /// Picks the directions of the cards the filter keeps, it is handed each card
+    /// along with the key its reviews are kept under.
+    pub fn select<T>(
+        &self,
+        deck: &str,
+        cards: Vec<T>,
+        mode: DirectionMode,
+        mut keep: impl FnMut(&T, &Key) -> bool,
+    ) -> Vec<Due<T>>
+    where
+        T: Kadeu + Clone,
+        T::Front: Display,
+    {
+        let mut due = vec![];
+        for card in cards {
+            for direction in mode.directions(card.reversible()) {
+                let key = Key {
+                    deck: deck.to_string(),
+                    card: card.id(),
+                    direction,
+                };
+                if keep(&card, &key) {
+                    due.push(Due {
+                        deck: key.deck,
+                        card: card.clone(),
+                        direction,
+                    });
+                }
+            }
+        }
+        due
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, Local};
+
+    use super::{Limits, Schedule};
+    use crate::game::{
+        flashcard::Flashcard, review::Review, Direction, DirectionMode, Kadeu, Score,
+    };
+
+    #[test]
+    fn directions_are_scheduled_apart() {
+        let now = Local::now();
+        let mut card = Flashcard::new("hund".to_string(), "dog".to_string());
+        card.set_reversible(Some(true));
+        let schedule = Schedule::from_reviews(&[Review {
+            deck: "german".to_string(),
+            card: card.id(),
+            direction: Direction::Forward,
+            score: Score::Hit,
+            hinted: false,
+            answer_ms: None,
+            reviewed_at: now - Duration::hours(1),
+        }]);
+
+        let due = schedule.due("german", vec![card], DirectionMode::Forward, false, now);
+        let directions: Vec<Direction> = due.into_iter().map(|due| due.direction).collect();
+        assert_eq!(directions, vec![Direction::Backward]);
+    }
+
+    #[test]
+    fn new_cards_are_limited_per_day() {
+        let now = Local::now();
+        let cards: Vec<_> = ["one", "two", "three"]
+            .into_iter()
+            .map(|front| Flashcard::new(front.to_string(), String::new()))
+            .collect();
+        let schedule = Schedule::from_reviews(&[Review {
+            deck: "deck".to_string(),
+            card: cards[0].id(),
+            direction: Direction::Forward,
+            score: Score::Miss,
+            hinted: false,
+            answer_ms: None,
+            reviewed_at: now,
+        }]);
+
+        let due = schedule.due("deck", cards, DirectionMode::Forward, false, now);
+        let limits = Limits {
+            new: Some(2),
+            reviews: None,
+        };
+        let due = schedule.limit(due, limits, now);
+        let counts = schedule.counts(&due);
+        // one new card was already seen today, the missed card is always kept
+        assert_eq!((counts.new, counts.learning, counts.due), (1, 1, 0));
+    }
+
+    #[test]
+    fn total_limits_hold_across_decks() {
+        let now = Local::now();
+        let card = |front: &str| Flashcard::new(front.to_string(), String::new());
+        let schedule = Schedule::from_reviews(&[Review {
+            deck: "spanish".to_string(),
+            card: card("uno").id(),
+            direction: Direction::Forward,
+            score: Score::Hit,
+            hinted: false,
+            answer_ms: None,
+            reviewed_at: now,
+        }]);
+        let limits = Limits {
+            new: Some(2),
+            reviews: None,
+        };
+        let mut due = vec![];
+        for (deck, fronts) in [("german", ["eins", "zwei"]), ("french", ["un", "deux"])] {
+            let cards = fronts.into_iter().map(card).collect();
+            let cards = schedule.due(deck, cards, DirectionMode::Forward, false, now);
+            due.extend(schedule.limit(cards, limits, now));
+        }
+        assert_eq!(due.len(), 4);
+        // the card studied from another deck today takes up one of the two
+        let due = schedule.limit_total(due, limits, now);
+        let fronts: Vec<&str> = due.iter().map(|due| due.card.front().as_str()).collect();
+        assert_eq!(fronts, vec!["eins"]);
+    }
+}
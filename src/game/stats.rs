@@ -0,0 +1,307 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use chrono::{DateTime, Duration, Local, NaiveDate};
+use serde::Serialize;
+
+use super::review::Review;
+use super::schedule::{Key, Schedule};
+use super::{Direction, Progress, Score};
+
+/// Days of reviews looked back over.
+pub const HISTORY_DAYS: usize = 30;
+/// Days of due cards looked ahead to.
+pub const FORECAST_DAYS: usize = 14;
+/// Days of reviews kept for the calendar, a year of whole weeks.
+pub const CALENDAR_DAYS: usize = 53 * 7;
+// cards with an interval this long are well known
+const MATURE_INTERVAL: i64 = 21;
+const HARDEST: usize = 10;
+
+/// How studying has gone so far and what is coming up.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct Stats {
+    pub reviews: usize,
+    /// Reviews given each day, oldest first and ending today.
+    pub per_day: Vec<Day>,
+    /// Share of reviews remembered out of those whose card was remembered the time before.
+    pub retention: Option<f64>,
+    /// Seconds a card was on display before it was revealed, on average.
+    pub answer_seconds: Option<f64>,
+    pub maturity: Maturity,
+    /// Cards falling due each day starting today, overdue cards count as today.
+    pub forecast: Vec<Day>,
+    /// The cards missed most often, worst first.
+    pub hardest: Vec<Hardest>,
+    pub streak: Streak,
+    /// Reviews given each day over the last year, for the calendar.
+    #[serde(skip)]
+    pub calendar: Vec<Day>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Day {
+    pub date: NaiveDate,
+    pub count: usize,
+}
+
+/// Cards sorted by how long they are spaced out for.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct Maturity {
+    pub new: usize,
+    /// Missed last time.
+    pub learning: usize,
+    pub young: usize,
+    /// Spaced out by three weeks or more.
+    pub mature: usize,
+}
+
+/// Days in a row with at least one review.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct Streak {
+    /// Up to today, or up to yesterday while nothing has been reviewed today.
+    pub current: usize,
+    pub longest: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Hardest {
+    pub deck: String,
+    pub card: String,
+    pub direction: Direction,
+    pub front: String,
+    pub reviews: usize,
+    pub misses: usize,
+}
+
+impl Stats {
+    /// Works out the statistics of the reviews, `cards` maps every direction of
+    /// the cards looked at to the text on their front.
+    pub fn new(reviews: &[Review], cards: &HashMap<Key, String>, now: DateTime<Local>) -> Self {
+        let schedule = Schedule::from_reviews(reviews);
+        let today = now.date_naive();
+
+        let calendar = activity(reviews, now, CALENDAR_DAYS);
+        let mut progress: HashMap<Key, Progress<Key>> = HashMap::new();
+        let (mut recalled, mut returned) = (0, 0);
+        let mut answers = vec![];
+        for review in reviews {
+            let key = Key::from(review);
+            let progress = progress
+                .entry(key.clone())
+                .or_insert_with(|| Progress::new(key));
+            if progress.score().is_some_and(|score| *score != Score::Miss) {
+                returned += 1;
+                if review.score != Score::Miss {
+                    recalled += 1;
+                }
+            }
+            progress.set_score(review.score);
+            answers.extend(review.answer_ms);
+        }
+
+        let mut maturity = Maturity::default();
+        let mut forecast: Vec<Day> = (0..FORECAST_DAYS)
+            .map(|ahead| Day {
+                date: today + Duration::days(ahead as i64),
+                count: 0,
+            })
+            .collect();
+        let mut hardest = vec![];
+        for (key, front) in cards {
+            let Some(state) = schedule.state(key) else {
+                maturity.new += 1;
+                continue;
+            };
+            match state.interval {
+                0 => maturity.learning += 1,
+                interval if interval < MATURE_INTERVAL => maturity.young += 1,
+                _ => maturity.mature += 1,
+            }
+            let ahead = (state.due.date_naive() - today).num_days().max(0) as usize;
+            if let Some(day) = forecast.get_mut(ahead) {
+                day.count += 1;
+            }
+            if state.misses > 0 {
+                hardest.push(Hardest {
+                    deck: key.deck.clone(),
+                    card: key.card.clone(),
+                    direction: key.direction,
+                    front: front.clone(),
+                    reviews: state.reviews,
+                    misses: state.misses,
+                });
+            }
+        }
+        hardest.sort_by(|a, b| {
+            b.misses
+                .cmp(&a.misses)
+                .then(a.reviews.cmp(&b.reviews))
+                .then(a.front.cmp(&b.front))
+        });
+        hardest.truncate(HARDEST);
+
+        Self {
+            reviews: reviews.len(),
+            per_day: calendar[CALENDAR_DAYS - HISTORY_DAYS..].to_vec(),
+            retention: (returned > 0).then(|| recalled as f64 / returned as f64),
+            answer_seconds: (!answers.is_empty())
+                .then(|| answers.iter().sum::<u64>() as f64 / answers.len() as f64 / 1000.0),
+            maturity,
+            forecast,
+            hardest,
+            streak: Streak::new(&calendar),
+            calendar,
+        }
+    }
+}
+
+impl Streak {
+    /// The streaks of days given oldest first and ending today.
+    pub fn new(days: &[Day]) -> Self {
+        let mut streak = Self::default();
+        let mut run = 0;
+        for day in days {
+            run = if day.count > 0 { run + 1 } else { 0 };
+            streak.longest = streak.longest.max(run);
+        }
+        // today still counts as part of the streak until it is over
+        let days = match days.split_last() {
+            Some((today, rest)) if today.count == 0 => rest,
+            _ => days,
+        };
+        streak.current = days.iter().rev().take_while(|day| day.count > 0).count();
+        streak
+    }
+}
+
+/// Reviews given each day over the last `days` days, oldest first and ending today.
+pub fn activity(reviews: &[Review], now: DateTime<Local>, days: usize) -> Vec<Day> {
+    let today = now.date_naive();
+    let mut activity: Vec<Day> = (0..days)
+        .rev()
+        .map(|ago| Day {
+            date: today - Duration::days(ago as i64),
+            count: 0,
+        })
+        .collect();
+    for review in reviews {
+        let ago = (today - review.reviewed_at.date_naive()).num_days();
+        if (0..days as i64).contains(&ago) {
+            activity[days - 1 - ago as usize].count += 1;
+        }
+    }
+    activity
+}
+
+impl fmt::Display for Stats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let recent: usize = self.per_day.iter().map(|day| day.count).sum();
+        writeln!(
+            f,
+            "Reviews      {} in the last {} days, {} in all",
+            recent, HISTORY_DAYS, self.reviews
+        )?;
+        match self.retention {
+            Some(retention) => writeln!(f, "Retention    {:.0}%", retention * 100.0)?,
+            None => writeln!(f, "Retention    -")?,
+        }
+        match self.answer_seconds {
+            Some(seconds) => writeln!(f, "Answer time  {:.1}s", seconds)?,
+            None => writeln!(f, "Answer time  -")?,
+        }
+        let maturity = self.maturity;
+        writeln!(
+            f,
+            "Cards        {} new, {} learning, {} young, {} mature",
+            maturity.new, maturity.learning, maturity.young, maturity.mature
+        )?;
+        let forecast: Vec<String> = self
+            .forecast
+            .iter()
+            .map(|day| day.count.to_string())
+            .collect();
+        writeln!(f, "Due          {} (from today)", forecast.join(" "))?;
+        writeln!(
+            f,
+            "Streak       {} days, {} at the longest",
+            self.streak.current, self.streak.longest
+        )?;
+        if !self.hardest.is_empty() {
+            writeln!(f, "Hardest")?;
+        }
+        for card in &self.hardest {
+            let front = card.front.lines().next().unwrap_or_default();
+            writeln!(f, "  {:>3} of {:<3} {}", card.misses, card.reviews, front)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use chrono::{Duration, Local};
+
+    use super::{Day, Stats, Streak};
+    use crate::game::{review::Review, schedule::Key, Direction, Score};
+
+    #[test]
+    fn retention_counts_cards_remembered_last_time() {
+        let now = Local::now();
+        let review = |card: &str, score, days| Review {
+            deck: "deck".to_string(),
+            card: card.to_string(),
+            direction: Direction::Forward,
+            score,
+            hinted: false,
+            answer_ms: Some(2000),
+            reviewed_at: now - Duration::days(days),
+        };
+        let reviews = [
+            review("one", Score::Hit, 3),
+            review("one", Score::Miss, 2),
+            review("one", Score::Hit, 1),
+            review("two", Score::Hit, 2),
+            review("two", Score::Hit, 1),
+        ];
+        let key = |card: &str| Key {
+            deck: "deck".to_string(),
+            card: card.to_string(),
+            direction: Direction::Forward,
+        };
+        let cards = HashMap::from([
+            (key("one"), "one".to_string()),
+            (key("two"), "two".to_string()),
+            (key("three"), "three".to_string()),
+        ]);
+
+        let stats = Stats::new(&reviews, &cards, now);
+        // the review after the miss doesn't count
+        assert_eq!(stats.retention, Some(0.5));
+        assert_eq!(stats.answer_seconds, Some(2.0));
+        assert_eq!(stats.maturity.new, 1);
+        assert_eq!(stats.maturity.young, 2);
+        assert_eq!(stats.per_day.last().map(|day| day.count), Some(0));
+        assert_eq!(stats.hardest.len(), 1);
+        assert_eq!(stats.hardest[0].front, "one");
+    }
+
+    #[test]
+    fn streak_waits_for_today() {
+        let today = Local::now().date_naive();
+        let days: Vec<Day> = [2, 0, 1, 3, 1, 0]
+            .into_iter()
+            .enumerate()
+            .map(|(i, count)| Day {
+                date: today - Duration::days(5 - i as i64),
+                count,
+            })
+            .collect();
+        let streak = Streak::new(&days);
+        assert_eq!((streak.current, streak.longest), (3, 3));
+        // nothing yet today, the day before still counts
+        assert_eq!(Streak::new(&days[..2]).current, 1);
+    }
+}
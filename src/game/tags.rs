@@ -0,0 +1,51 @@
+/// Picks cards by their tags, tags are compared ignoring case.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TagFilter {
+    include: Vec<String>,
+    exclude: Vec<String>,
+}
+
+impl TagFilter {
+    pub fn new(include: &[String], exclude: &[String]) -> Self {
+        let normalize = |tags: &[String]| tags.iter().map(|tag| tag.to_lowercase()).collect();
+        Self {
+            include: normalize(include),
+            exclude: normalize(exclude),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.include.is_empty() && self.exclude.is_empty()
+    }
+
+    /// True if the tags have one of the included tags, when there are any,
+    /// and none of the excluded ones.
+    pub fn matches<'a>(&self, tags: impl IntoIterator<Item = &'a String>) -> bool {
+        let tags: Vec<String> = tags.into_iter().map(|tag| tag.to_lowercase()).collect();
+        let included = self.include.is_empty() || self.include.iter().any(|tag| tags.contains(tag));
+        included && !self.exclude.iter().any(|tag| tags.contains(tag))
+    }
+}
+
+/// Splits tags written on one line, separated by commas or spaces.
+pub fn parse(line: &str) -> Vec<String> {
+    line.split([',', ' '])
+        .map(str::trim)
+        .filter(|tag| !tag.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse, TagFilter};
+
+    #[test]
+    fn include_and_exclude() {
+        let filter = TagFilter::new(&parse("tcp, dns"), &parse("Hard"));
+        assert!(filter.matches(&parse("networking TCP")));
+        assert!(!filter.matches(&parse("networking tcp hard")));
+        assert!(!filter.matches(&parse("networking")));
+        assert!(TagFilter::default().matches(&parse("")));
+    }
+}
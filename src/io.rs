@@ -3,11 +3,18 @@ use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use serde_json;
 use std::{
     ffi::{self, OsStr},
-    fs::{self, File, OpenOptions},
+    fs::{self, File},
+    io::Write,
     path::{Path, PathBuf},
 };
 
-use crate::{app::Deck, cli::Config};
+use crate::{
+    app::Deck,
+    cli::{Config, ExportFormat, OnConflict},
+    error::{line_column, Error, Result},
+    game::query::glob,
+    Entry,
+};
 
 #[derive(Debug, Clone)]
 pub enum ImportEntry {
@@ -32,6 +39,8 @@ impl ImportEntry {
 
 pub enum FileType {
     Json(PathBuf),
+    Yaml(PathBuf),
+    Toml(PathBuf),
 }
 
 pub struct Directories {
@@ -53,10 +62,184 @@ pub fn list_directory(path: &PathBuf) -> std::io::Result<Vec<ImportEntry>> {
     Ok(entries)
 }
 
+/// Every file under the directory, however deep, which loads as a `T`. Sorted by path.
+pub fn deck_files<T: DeserializeOwned>(directory: &Path) -> std::io::Result<Vec<PathBuf>> {
+    let mut files = vec![];
+    let mut directories = vec![directory.to_path_buf()];
+    while let Some(directory) = directories.pop() {
+        for entry in fs::read_dir(directory)? {
+            let path = entry?.path();
+            if path.is_dir() {
+                directories.push(path);
+            } else if FileType::from_path(&path).load::<T>().is_ok() {
+                files.push(path);
+            }
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// How a deck ended up in the imports directory, see `import_deck`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Imported {
+    Added,
+    Overwritten,
+    /// Saved next to the deck it clashed with under this name.
+    Renamed(PathBuf),
+    /// This many entries were added to the deck already there.
+    Merged(usize),
+    Skipped,
+}
+
+// files in a directory which are taken to be decks
+pub(crate) const DECK_EXTENSIONS: [&str; 4] = ["json", "yaml", "yml", "toml"];
+
+/// The files to import from a deck, a directory or a glob such as `decks/*/*.json`,
+/// each with where it goes under the imports directory.
+/// A directory keeps its name as a collection, a glob keeps the directories it matched in.
+pub fn import_sources(source: &Path) -> Result<Vec<(PathBuf, PathBuf)>> {
+    let pattern = source.to_string_lossy();
+    if pattern.contains(['*', '?']) {
+        // walk from the last directory before the wildcards
+        let mut base = PathBuf::new();
+        let mut rest = vec![];
+        for part in source.iter() {
+            let part = part.to_string_lossy();
+            if rest.is_empty() && !part.contains(['*', '?']) {
+                base.push(part.as_ref());
+            } else {
+                rest.push(part.to_string());
+            }
+        }
+        let rest = rest.join("/");
+        let walk_from = if base.as_os_str().is_empty() {
+            PathBuf::from(".")
+        } else {
+            base.clone()
+        };
+        let mut sources = vec![];
+        for file in deck_paths(&walk_from).map_err(Error::io(&walk_from))? {
+            let relative = file.strip_prefix(&walk_from).unwrap_or(&file).to_path_buf();
+            let parts: Vec<String> = relative
+                .iter()
+                .map(|part| part.to_string_lossy().to_string())
+                .collect();
+            if glob(&rest, &parts.join("/")) {
+                sources.push((file, relative));
+            }
+        }
+        return Ok(sources);
+    }
+
+    if source.is_dir() {
+        let name = source
+            .canonicalize()
+            .map_err(Error::io(source))?
+            .file_name()
+            .map(PathBuf::from)
+            .unwrap_or_default();
+        let sources = deck_paths(source)
+            .map_err(Error::io(source))?
+            .into_iter()
+            .map(|file| {
+                let relative = name.join(file.strip_prefix(source).unwrap_or(&file));
+                (file, relative)
+            })
+            .collect();
+        return Ok(sources);
+    }
+
+    match source.file_name() {
+        Some(name) => Ok(vec![(source.to_path_buf(), PathBuf::from(name))]),
+        None => Err(Error::Source(source.to_path_buf())),
+    }
+}
+
+/// Every file under the directory with the extension of a deck, whether it loads or not.
+pub fn deck_paths(directory: &Path) -> std::io::Result<Vec<PathBuf>> {
+    let mut files = files_under(directory)?;
+    files.retain(|file| {
+        file.extension()
+            .is_some_and(|ext| DECK_EXTENSIONS.iter().any(|deck| ext == *deck))
+    });
+    Ok(files)
+}
+
+/// Every file under the directory, however deep. Sorted by path.
+fn files_under(directory: &Path) -> std::io::Result<Vec<PathBuf>> {
+    let mut files = vec![];
+    let mut directories = vec![directory.to_path_buf()];
+    while let Some(directory) = directories.pop() {
+        for entry in fs::read_dir(directory)? {
+            let path = entry?.path();
+            if path.is_dir() {
+                directories.push(path);
+            } else {
+                files.push(path);
+            }
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// Copies a deck to the destination once it has loaded, in the format of the destination.
+/// `conflict` decides what happens when there is a deck there already.
+pub fn import_deck(
+    source: &Path,
+    destination: &Path,
+    conflict: OnConflict,
+) -> std::io::Result<Imported> {
+    let deck: Deck<Entry> = FileType::from_path(source).load()?;
+    if let Some(parent) = destination.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    if !destination.exists() {
+        FileType::from_path(destination).save(&deck)?;
+        return Ok(Imported::Added);
+    }
+    match conflict {
+        OnConflict::Skip => Ok(Imported::Skipped),
+        OnConflict::Overwrite => {
+            FileType::from_path(destination).save(&deck)?;
+            Ok(Imported::Overwritten)
+        }
+        OnConflict::Rename => {
+            let stem = destination
+                .file_stem()
+                .unwrap_or_default()
+                .to_string_lossy();
+            let extension = destination
+                .extension()
+                .unwrap_or_default()
+                .to_string_lossy();
+            let path = (2..)
+                .map(|n| destination.with_file_name(format!("{}-{}.{}", stem, n, extension)))
+                .find(|path| !path.exists())
+                .unwrap_or_default();
+            FileType::from_path(&path).save(&deck)?;
+            Ok(Imported::Renamed(path))
+        }
+        OnConflict::Merge => {
+            let mut existing: Deck<Entry> = FileType::from_path(destination).load()?;
+            let added = existing.merge(deck);
+            FileType::from_path(destination).save(&existing)?;
+            Ok(Imported::Merged(added))
+        }
+    }
+}
+
+/// The name a deck is recorded under in the review log.
+pub fn deck_name(path: &Path) -> String {
+    let path = fs::canonicalize(path).unwrap_or(path.to_path_buf());
+    path.to_string_lossy().to_string()
+}
+
 pub fn convert_to_path<T: DeserializeOwned + Serialize>(
     source: FileType,
     destination: FileType,
-) -> std::io::Result<()> {
+) -> Result<()> {
     let item: T = source.load()?;
     destination.save(&item)
 }
@@ -73,27 +256,89 @@ impl FileType {
     pub fn json(path: &PathBuf) -> Self {
         Self::Json(path.clone())
     }
-    pub fn load<T: DeserializeOwned>(self) -> std::io::Result<T> {
+
+    /// Picks the serialization format from the extension of the path.
+    /// Anything that isn't recognised as yaml or toml is treated as json.
+    pub fn from_path(path: &Path) -> Self {
+        let path = path.to_path_buf();
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("yaml") | Some("yml") => Self::Yaml(path),
+            Some("toml") => Self::Toml(path),
+            _ => Self::Json(path),
+        }
+    }
+
+    /// Like `from_path`, but only for the extensions decks are kept under.
+    pub fn detect(path: &Path) -> Result<Self> {
+        let known = path
+            .extension()
+            .is_some_and(|ext| DECK_EXTENSIONS.iter().any(|deck| ext == *deck));
+        match known {
+            true => Ok(Self::from_path(path)),
+            false => Err(Error::Format(path.to_path_buf())),
+        }
+    }
+
+    /// The name of the format, as used for the extension.
+    pub fn format(&self) -> &'static str {
         match self {
-            Self::Json(path) => {
-                let reader = OpenOptions::new().read(true).open(path)?;
-                let item: T = serde_json::from_reader(reader)?;
-                Ok(item)
-            }
+            Self::Json(_) => "json",
+            Self::Yaml(_) => "yaml",
+            Self::Toml(_) => "toml",
         }
     }
 
-    pub fn save<T: Serialize>(self, item: &T) -> std::io::Result<()> {
+    pub fn path(&self) -> &Path {
         match self {
-            Self::Json(path) => {
-                let writer = OpenOptions::new()
-                    .create(true)
-                    .write(true)
-                    .truncate(true)
-                    .open(path)?;
-                let _ = serde_json::to_writer(writer, item)?;
-                Ok(())
+            Self::Json(path) | Self::Yaml(path) | Self::Toml(path) => path,
+        }
+    }
+
+    /// Parse errors say where in the file they are.
+    pub fn load<T: DeserializeOwned>(self) -> Result<T> {
+        let path = self.path();
+        let buf = fs::read_to_string(path).map_err(Error::io(path))?;
+        match &self {
+            Self::Json(_) => serde_json::from_str(&buf)
+                .map_err(|e| Error::parse(path, Some((e.line(), e.column())), &e)),
+            Self::Yaml(_) => serde_yaml::from_str(&buf).map_err(|e| {
+                let position = e.location().map(|at| (at.line(), at.column()));
+                Error::parse(path, position, &e)
+            }),
+            Self::Toml(_) => toml::from_str(&buf).map_err(|e| {
+                let position = e.span().map(|span| line_column(&buf, span.start));
+                Error::parse(path, position, e.message())
+            }),
+        }
+    }
+
+    pub fn save<T: Serialize>(self, item: &T) -> Result<()> {
+        let path = self.path();
+        // serialize up front so a failure doesn't leave a truncated deck behind.
+        let buf = match &self {
+            Self::Json(_) => serde_json::to_string_pretty(item).map_err(|e| e.to_string()),
+            Self::Yaml(_) => serde_yaml::to_string(item).map_err(|e| e.to_string()),
+            Self::Toml(_) => toml::to_string_pretty(item).map_err(|e| e.to_string()),
+        }
+        .map_err(|e| Error::parse(path, None, format!("couldn't be written: {}", e)))?;
+        fs::write(path, buf).map_err(Error::io(path))
+    }
+}
+
+/// Writes the records as a json array, or as csv with a header row.
+pub fn write_records<T: Serialize>(
+    records: &[T],
+    format: ExportFormat,
+    writer: impl Write,
+) -> std::io::Result<()> {
+    match format {
+        ExportFormat::Json => Ok(serde_json::to_writer_pretty(writer, records)?),
+        ExportFormat::Csv => {
+            let mut writer = csv::Writer::from_writer(writer);
+            for record in records {
+                writer.serialize(record).map_err(std::io::Error::other)?;
             }
+            writer.flush()
         }
     }
 }
@@ -102,3 +347,90 @@ pub fn list_files<T>(path: &PathBuf) -> Vec<T> {
     todo!("List the files here");
     vec![]
 }
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::{import_deck, import_sources, Imported};
+    use crate::{app::Deck, cli::OnConflict, io::FileType, Entry};
+
+    #[test]
+    fn sources_keep_what_they_matched() {
+        let directory = std::env::temp_dir().join(format!("kadeu-sources-{}", std::process::id()));
+        let src = directory.join("src");
+        fs::create_dir_all(src.join("net")).unwrap();
+        fs::create_dir_all(src.join("web/old")).unwrap();
+        for file in [
+            "net/d.json",
+            "net/README.md",
+            "web/e.yaml",
+            "web/old/f.toml",
+        ] {
+            fs::write(src.join(file), "").unwrap();
+        }
+
+        // a glob walks from the directory before its wildcards and keeps the rest of the path
+        let sources = import_sources(&src.join("*/*")).unwrap();
+        assert_eq!(
+            sources,
+            vec![
+                (src.join("net/d.json"), "net/d.json".into()),
+                (src.join("web/e.yaml"), "web/e.yaml".into()),
+            ]
+        );
+        let sources = import_sources(&directory.join("*/web/*/*.toml")).unwrap();
+        assert_eq!(
+            sources,
+            vec![(src.join("web/old/f.toml"), "src/web/old/f.toml".into())]
+        );
+        // a directory becomes a collection of its own name
+        let sources = import_sources(&src.join("web")).unwrap();
+        assert_eq!(
+            sources,
+            vec![
+                (src.join("web/e.yaml"), "web/e.yaml".into()),
+                (src.join("web/old/f.toml"), "web/old/f.toml".into()),
+            ]
+        );
+        // a deck goes straight into the imports
+        let sources = import_sources(&src.join("net/d.json")).unwrap();
+        assert_eq!(sources, vec![(src.join("net/d.json"), "d.json".into())]);
+        fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn conflicts_are_renamed_or_merged() {
+        let directory = std::env::temp_dir().join(format!("kadeu-import-{}", std::process::id()));
+        let source = directory.join("source.json");
+        let destination = directory.join("imports/deck.json");
+        fs::create_dir_all(&directory).unwrap();
+        fs::write(
+            &source,
+            r#"{"title": "a", "cards": [{"front": "one", "back": "1"}]}"#,
+        )
+        .unwrap();
+        let added = import_deck(&source, &destination, OnConflict::Skip).unwrap();
+        assert_eq!(added, Imported::Added);
+        let skipped = import_deck(&source, &destination, OnConflict::Skip).unwrap();
+        assert_eq!(skipped, Imported::Skipped);
+        let overwritten = import_deck(&source, &destination, OnConflict::Overwrite).unwrap();
+        assert_eq!(overwritten, Imported::Overwritten);
+
+        let renamed = import_deck(&source, &destination, OnConflict::Rename).unwrap();
+        assert_eq!(
+            renamed,
+            Imported::Renamed(directory.join("imports/deck-2.json"))
+        );
+        fs::write(
+            &source,
+            r#"{"title": "a", "cards": [{"front": "one", "back": "1"}, {"front": "two", "back": "2"}]}"#,
+        )
+        .unwrap();
+        let merged = import_deck(&source, &destination, OnConflict::Merge).unwrap();
+        assert_eq!(merged, Imported::Merged(1));
+        let deck: Deck<Entry> = FileType::from_path(&destination).load().unwrap();
+        assert_eq!(deck.cards().len(), 2);
+        fs::remove_dir_all(&directory).unwrap();
+    }
+}
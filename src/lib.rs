@@ -1,22 +1,158 @@
 pub mod app;
+pub mod check;
 pub mod cli;
+pub mod error;
 pub mod game;
 pub mod io;
+pub mod merge;
 // pub mod tui;
 pub mod ui;
 //pub mod tui;
 //mod store;
 use crate::game::Kadeu;
 use app::{Card, Deck};
+use cli::SplitBy;
+use game::choice::{self, Choice};
+use game::cloze::Cloze;
 use game::engine::Strategy;
 use game::flashcard::{self};
-use serde::Deserialize;
+use game::note::Note;
+use game::Expand;
+use rand::{rngs::StdRng, SeedableRng};
+use serde::{Deserialize, Serialize};
 use serde_json;
 
+use std::collections::HashSet;
 use std::fmt::Display;
 
 pub type Flashcard = flashcard::Flashcard<String, String>;
 
+/// A single entry of a deck file, which kind is decided by the fields it has.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(untagged)]
+pub enum Entry {
+    Choice(Choice),
+    Cloze(Cloze),
+    Note(Note),
+    Flashcard(Flashcard),
+}
+
+impl Expand for Entry {
+    type Item = Flashcard;
+
+    fn expand(&self) -> Vec<Self::Item> {
+        match self {
+            Self::Choice(choice) => choice.expand(),
+            Self::Cloze(cloze) => cloze.expand(),
+            Self::Note(note) => note.expand(),
+            Self::Flashcard(card) => card.expand(),
+        }
+    }
+}
+
+impl Entry {
+    pub fn tags(&self) -> &[String] {
+        match self {
+            Self::Choice(choice) => choice.tags(),
+            Self::Cloze(cloze) => cloze.tags(),
+            Self::Note(note) => note.tags(),
+            Self::Flashcard(card) => card.tags(),
+        }
+    }
+
+    pub fn set_tags(&mut self, tags: Vec<String>) {
+        match self {
+            Self::Choice(choice) => choice.set_tags(tags),
+            Self::Cloze(cloze) => cloze.set_tags(tags),
+            Self::Note(note) => note.set_tags(tags),
+            Self::Flashcard(card) => card.set_tags(tags),
+        }
+    }
+}
+
+impl Deck<Entry> {
+    /// The cards made from an entry, notes are rendered through the templates of the deck.
+    pub fn expand_entry(&self, entry: &Entry) -> Vec<Flashcard> {
+        match entry {
+            Entry::Note(note) if !self.templates().is_empty() => note.cards(self.templates()),
+            entry => entry.expand(),
+        }
+    }
+
+    /// Adds the entries of the other deck which make cards this deck doesn't have yet.
+    /// Returns how many entries were added.
+    pub fn merge(&mut self, other: Deck<Entry>) -> usize {
+        let mut ids: HashSet<String> = self
+            .cards()
+            .into_iter()
+            .flat_map(|entry| self.expand_entry(entry))
+            .map(|card| card.id())
+            .collect();
+        let mut added = 0;
+        for entry in other.cards() {
+            let cards = other.expand_entry(entry);
+            if cards.iter().all(|card| ids.contains(&card.id())) {
+                continue;
+            }
+            ids.extend(cards.iter().map(|card| card.id()));
+            self.cards_mut().push(entry.clone());
+            added += 1;
+        }
+        added
+    }
+}
+
+impl Deck<Entry> {
+    /// Breaks the deck into smaller decks with its settings, each with the name of its file.
+    /// Entries go by their first tag, untagged ones together, or in chunks of the same size.
+    pub fn split(&self, by: SplitBy) -> Vec<(String, Deck<Entry>)> {
+        let mut pieces: Vec<(String, Vec<Entry>)> = vec![];
+        match by {
+            SplitBy::Tag => {
+                for entry in self.cards() {
+                    let name = entry
+                        .tags()
+                        .first()
+                        .map(|tag| tag.replace(['/', '\\', ' '], "-"))
+                        .unwrap_or("untagged".to_string());
+                    match pieces.iter_mut().find(|(piece, _)| *piece == name) {
+                        Some((_, entries)) => entries.push(entry.clone()),
+                        None => pieces.push((name, vec![entry.clone()])),
+                    }
+                }
+            }
+            SplitBy::Chunk(size) => {
+                let entries: Vec<Entry> = self.cards().into_iter().cloned().collect();
+                for (i, chunk) in entries.chunks(size.max(1)).enumerate() {
+                    pieces.push((format!("part-{}", i + 1), chunk.to_vec()));
+                }
+            }
+        }
+        pieces
+            .into_iter()
+            .map(|(name, entries)| {
+                let mut deck = self.clone().with_cards(entries);
+                deck.set_title(&format!("{} ({})", self.title(), name));
+                (name, deck)
+            })
+            .collect()
+    }
+}
+
+/// Expands the entries of a deck into the cards of a session.
+/// The seed decides which distractors multiple choice cards are dealt and their order.
+pub fn deal(deck: Deck<Entry>, seed: u64) -> Deck<Flashcard> {
+    let mut cards: Vec<Flashcard> = deck
+        .cards()
+        .into_iter()
+        .flat_map(|entry| deck.expand_entry(entry))
+        .collect();
+    let pool: Vec<String> = cards.iter().map(|card| card.back().clone()).collect();
+    let mut rng = StdRng::seed_from_u64(seed);
+    choice::deal(&mut cards, &pool, &mut rng);
+    deck.with_cards(cards)
+}
+
 impl<T, U> Kadeu for Card<T, U>
 where
     T: Display,
@@ -78,3 +214,41 @@ mod strategies {
     #[cfg(test)]
     mod test {}
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::app::Deck;
+    use crate::cli::SplitBy;
+    use crate::game::Kadeu;
+    use crate::{Entry, Flashcard};
+
+    #[test]
+    fn split_keeps_card_ids() {
+        let entry = |front: &str, tags: &[&str]| {
+            let mut entry = Entry::Flashcard(Flashcard::new(front.to_string(), "back".to_string()));
+            entry.set_tags(tags.iter().map(|tag| tag.to_string()).collect());
+            entry
+        };
+        let deck = Deck::<Entry>::new("networking").with_cards(vec![
+            entry("SYN", &["tcp"]),
+            entry("DNS", &[]),
+            entry("FIN", &["tcp", "flags"]),
+        ]);
+
+        let pieces = deck.split(SplitBy::Tag);
+        let names: Vec<&str> = pieces.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names, vec!["tcp", "untagged"]);
+        assert_eq!(pieces[0].1.title(), "networking (tcp)");
+        let id = |deck: &Deck<Entry>| deck.expand_entry(deck.cards()[0])[0].id();
+        assert_eq!(id(&pieces[0].1), id(&deck));
+
+        let sizes: Vec<usize> = deck
+            .split(SplitBy::Chunk(2))
+            .iter()
+            .map(|(_, piece)| piece.cards().len())
+            .collect();
+        assert_eq!(sizes, vec![2, 1]);
+        assert_eq!("chunk=2".parse(), Ok(SplitBy::Chunk(2)));
+        assert!("chunk=0".parse::<SplitBy>().is_err());
+    }
+}
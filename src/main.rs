@@ -1,116 +1,1248 @@
+use chrono::{DateTime, Local, NaiveDate};
 use clap::Parser;
-use crossterm::terminal::{disable_raw_mode, enable_raw_mode};
 use kadeu::app::Deck;
-use kadeu::cli::{self, Subcommand};
+use kadeu::check::{check, Location};
+use kadeu::cli::{self, Format, Keep, OnConflict, SplitBy, StatsCommand, Subcommand};
+use kadeu::error::{self, Error};
 use kadeu::game::engine::ui::FlashcardApp;
-use kadeu::io::{convert_to_path, list_directory, FileType, ImportEntry};
+use kadeu::game::query::{Candidate, Query, QUERY_EXTENSION};
+use kadeu::game::review::{Record, ReviewLog};
+use kadeu::game::schedule::{Counts, Due, Schedule};
+use kadeu::game::stats::{activity, Stats, CALENDAR_DAYS};
+use kadeu::game::tags::TagFilter;
+use kadeu::game::Kadeu;
+use kadeu::io::{
+    deck_files, deck_name, deck_paths, import_deck, import_sources, list_directory, write_records,
+    FileType, ImportEntry, Imported,
+};
+use kadeu::merge::{duplicates, Place};
 // use kadeu::tui::{App, Card};
 use kadeu::ui::deck_browser::DeckBrowser;
-use kadeu::ui::{AppHandler, Exit};
-use kadeu::Flashcard;
+use kadeu::ui::dialog::Dialog;
+use kadeu::ui::editor::DeckEditor;
+use kadeu::ui::home::{DeckSummary, Home};
+use kadeu::ui::stats::StatsApp;
+use kadeu::ui::terminal::TerminalGuard;
+use kadeu::ui::{AppHandler, KadeuApp, Screen};
+use kadeu::{deal, Entry, Flashcard};
 use ratatui::prelude::CrosstermBackend;
 use ratatui::Terminal;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::fs;
 use std::io;
-use std::io::{stdout, Stdout};
+use std::io::{stdout, BufRead, Stdout, Write};
+use std::path::{Path, PathBuf};
 
 // Governs how subcollection flashcard should be accessed.
 const IFS: &str = "/";
 
-enum Apps {
-    DeckBrowser,
-    FlashcardApp,
-}
-
 fn crossterm_terminal() -> std::io::Result<Terminal<CrosstermBackend<Stdout>>> {
     Terminal::new(CrosstermBackend::new(stdout()))
 }
 
-fn main() -> io::Result<()> {
-    let args = cli::Args::parse();
-    let mut subcommand = args.subcommand.clone().unwrap_or_default();
+/// Deals the decks into one session of the cards that match the tag filters,
+/// directories bring in every deck under them. Without a query only the cards that are due are studied.
+fn session(
+    paths: &[PathBuf],
+    title: Option<&str>,
+    query: Option<&Query>,
+    args: &cli::Args,
+    config: &cli::Config,
+    seed: u64,
+) -> io::Result<FlashcardApp<Flashcard>> {
+    let filter = TagFilter::new(&args.tags, &args.exclude_tags);
+    let log = ReviewLog::new(config.review_log());
+    let reviews = log.load()?;
+    let schedule = Schedule::from_reviews(&reviews);
+    let imports = config.import_directory();
+    let now = Local::now();
 
-    //let mut browser = None;
-    let config = args.read_config()?;
-    let mut browser = DeckBrowser::try_from(config.import_directory())?;
-    enable_raw_mode()?;
-    let terminal = Terminal::new(CrosstermBackend::new(stdout()))?;
-    let mut app = AppHandler::from(terminal);
-
-    loop {
-        match &subcommand {
-            Subcommand::Browse => {
-                // makes a new browser if none exists
-                // otherwise uses whatever browser has already been produced
-                // prevents the UI from resetting to root after the user finishes browsing.
-
-                let _action = app.run(&mut browser)?;
-                if let Exit::Quit = _action {
-                    break;
-                }
+    // collections are named after their directory, single decks after their title
+    let mut titles = vec![];
+    let mut files = vec![];
+    for path in paths {
+        if path.is_dir() {
+            let name = path.file_name().unwrap_or(path.as_os_str());
+            titles.push(name.to_string_lossy().to_string());
+            files.extend(deck_files::<Deck<Entry>>(path)?);
+        } else {
+            files.push(path.clone());
+        }
+    }
 
-                if browser.is_deck::<Flashcard>() {
-                    let path = browser.current_path();
-                    subcommand = Subcommand::Source { path }
-                }
+    let mut decks = HashMap::new();
+    let mut markdown = false;
+    let mut skipped = 0;
+    let mut items = vec![];
+    for path in &files {
+        let deck: Deck<Entry> = FileType::from_path(path).load()?;
+        skipped += deck
+            .cards()
+            .into_iter()
+            .filter(|entry| deck.expand_entry(entry).is_empty())
+            .count();
+        let deck = deal(deck, seed);
+        let name = deck_name(path);
+        if paths.contains(path) {
+            titles.push(deck.title().to_string());
+        }
+        decks.insert(name.clone(), deck.title().to_string());
+        markdown |= deck.markdown();
+        let limits = config.limits(&deck);
+        let tags = deck.tags().to_vec();
+        let cards: Vec<Flashcard> = deck
+            .into_cards()
+            .into_iter()
+            .filter(|card| filter.matches(tags.iter().chain(card.tags())))
+            .collect();
+        let Some(query) = query else {
+            let due = schedule.due(&name, cards, args.direction, args.all, now);
+            if args.all {
+                items.extend(due);
+            } else {
+                items.extend(schedule.limit(due, limits, now));
+            }
+            continue;
+        };
+        let relative = path.strip_prefix(&imports).unwrap_or(path);
+        let relative: Vec<String> = relative
+            .iter()
+            .map(|part| part.to_string_lossy().to_string())
+            .collect();
+        let relative = relative.join(IFS);
+        items.extend(schedule.select(&name, cards, args.direction, |card, key| {
+            let candidate = Candidate {
+                deck: &relative,
+                tags: tags.iter().chain(card.tags()).collect(),
+                text: format!("{}\n{}", card.front(), card.back()),
+                state: schedule.state(key),
+            };
+            query.matches(&candidate, now)
+        }));
+    }
+    // filtered decks take in whatever matches, like a single deck
+    if query.is_none() && !args.all {
+        items = schedule.limit_total(items, config.global_limits(), now);
+    }
+    let title = match title {
+        Some(title) => title.to_string(),
+        None => titles.join(", "),
+    };
+    Ok(FlashcardApp::new(&title, markdown, items)
+        .with_log(log)
+        .with_deck_titles(decks)
+        .with_skipped(skipped)
+        .with_hint_grade(config.hint_grade())
+        .with_calendar(activity(&reviews, now, CALENDAR_DAYS), config.daily_goal()))
+}
+
+/// What is waiting today in every imported deck, within the daily limits.
+fn summaries(args: &cli::Args, config: &cli::Config) -> io::Result<Vec<DeckSummary>> {
+    let log = ReviewLog::new(config.review_log());
+    let schedule = Schedule::from_reviews(&log.load()?);
+    let imports = config.import_directory();
+    let now = Local::now();
+    let mut paths = vec![];
+    let mut due = vec![];
+    for path in deck_files::<Deck<Entry>>(&imports)? {
+        let deck: Deck<Entry> = FileType::from_path(&path).load()?;
+        due.extend(due_today(&path, deck, &schedule, args, config, now));
+        paths.push(path);
+    }
+    // as many as a session of every deck would take in
+    let due = schedule.limit_total(due, config.global_limits(), now);
+    let summaries = paths
+        .into_iter()
+        .map(|path| {
+            let name = deck_name(&path);
+            let due: Vec<_> = due.iter().filter(|due| due.deck == name).cloned().collect();
+            let relative = path.strip_prefix(&imports).unwrap_or(&path);
+            DeckSummary {
+                name: relative.to_string_lossy().to_string(),
+                counts: schedule.counts(&due),
+                path,
+            }
+        })
+        .collect();
+    Ok(summaries)
+}
+
+/// The cards waiting in a deck today, within its daily limits.
+fn due_today(
+    path: &Path,
+    deck: Deck<Entry>,
+    schedule: &Schedule,
+    args: &cli::Args,
+    config: &cli::Config,
+    now: DateTime<Local>,
+) -> Vec<Due<Flashcard>> {
+    // the seed only shuffles options, it doesn't change which cards are due
+    let deck = deal(deck, 0);
+    let limits = config.limits(&deck);
+    let name = deck_name(path);
+    let due = schedule.due(&name, deck.into_cards(), args.direction, false, now);
+    schedule.limit(due, limits, now)
+}
+
+/// One entry of `kadeu show`.
+#[derive(Serialize)]
+struct Listing {
+    /// The name to run it by, without the extension of a deck.
+    name: String,
+    #[serde(serialize_with = "lossy")]
+    path: PathBuf,
+    kind: ListingKind,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    title: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    cards: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    format: Option<&'static str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    due: Option<Counts>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    entries: Vec<Listing>,
+}
+
+#[derive(Serialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+enum ListingKind {
+    Collection,
+    Deck,
+    /// A filtered deck saved by `kadeu study --save`.
+    Query,
+    File,
+}
+
+/// Writes a path as a string even when it isn't valid unicode.
+fn lossy<S: serde::Serializer>(path: &Path, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&path.to_string_lossy())
+}
+
+/// The entries of a directory sorted by name, going into collections when `tree` is set.
+fn listing(
+    directory: &Path,
+    tree: bool,
+    schedule: &Schedule,
+    args: &cli::Args,
+    config: &cli::Config,
+) -> io::Result<Vec<Listing>> {
+    let now = Local::now();
+    let mut listings = vec![];
+    for entry in list_directory(&directory.to_path_buf())? {
+        let (path, kind) = match entry {
+            ImportEntry::Collection(path) => (path, ListingKind::Collection),
+            ImportEntry::File(path)
+                if path.extension().is_some_and(|ext| ext == QUERY_EXTENSION) =>
+            {
+                (path, ListingKind::Query)
+            }
+            ImportEntry::File(path) => (path, ListingKind::File),
+        };
+        let name = match kind {
+            ListingKind::Collection => path.file_name(),
+            _ => path.file_stem(),
+        };
+        let name = name
+            .unwrap_or(path.as_os_str())
+            .to_string_lossy()
+            .to_string();
+        let mut listing = Listing {
+            name,
+            path,
+            kind,
+            title: None,
+            cards: None,
+            format: None,
+            due: None,
+            entries: vec![],
+        };
+        if listing.kind == ListingKind::Collection && tree {
+            listing.entries = self::listing(&listing.path, tree, schedule, args, config)?;
+        }
+        if listing.kind == ListingKind::File {
+            let file = FileType::from_path(&listing.path);
+            let format = file.format();
+            if let Ok(deck) = file.load::<Deck<Entry>>() {
+                listing.kind = ListingKind::Deck;
+                listing.title = Some(deck.title().to_string());
+                listing.cards = Some(
+                    deck.cards()
+                        .iter()
+                        .map(|entry| deck.expand_entry(entry).len())
+                        .sum(),
+                );
+                listing.format = Some(format);
+                let due = due_today(&listing.path, deck, schedule, args, config, now);
+                listing.due = Some(schedule.counts(&due));
+            }
+        }
+        listings.push(listing);
+    }
+    listings.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(listings)
+}
+
+/// Writes the listing one entry a line, collections end in `/` and their entries are indented.
+fn write_listing<W: Write>(
+    listings: &[Listing],
+    long: bool,
+    depth: usize,
+    output: &mut W,
+) -> io::Result<()> {
+    let indent = "  ".repeat(depth);
+    for listing in listings {
+        let name = match listing.kind {
+            ListingKind::Collection => format!("{}{}/", indent, listing.name),
+            _ => format!("{}{}", indent, listing.name),
+        };
+        match (&listing.title, listing.cards, listing.format, listing.due) {
+            (Some(title), Some(cards), Some(format), Some(due)) if long => writeln!(
+                output,
+                "{:<24} {:<24} {:>5} cards  {:<4}  {} new  {} learning  {} due",
+                name, title, cards, format, due.new, due.learning, due.due
+            )?,
+            _ => writeln!(output, "{}", name)?,
+        }
+        write_listing(&listing.entries, long, depth + 1, output)?;
+    }
+    Ok(())
+}
+
+/// The imported deck or collection by its name, e.g. `networking/tcp`.
+fn import_path(config: &cli::Config, name: &str) -> PathBuf {
+    let mut filepath = config.import_directory();
+    name.split(IFS).for_each(|path| {
+        filepath.push(path);
+    });
+    // a collection is studied as a whole, a deck is a json file
+    if !filepath.is_dir() {
+        filepath.set_extension("json");
+    }
+    filepath
+}
+
+/// The files of a deck or collection, given as a path or an imported name,
+/// or of every imported deck. Returns them along with what they are called.
+fn scope(deck: Option<&Path>, config: &cli::Config) -> io::Result<(String, Vec<PathBuf>)> {
+    let scope = match deck {
+        Some(deck) => {
+            let path = resolve(deck, config);
+            let files = if path.is_dir() {
+                deck_files::<Deck<Entry>>(&path)?
+            } else {
+                vec![path]
+            };
+            (deck.to_string_lossy().to_string(), files)
+        }
+        None => (
+            "All decks".to_string(),
+            deck_files::<Deck<Entry>>(&config.import_directory())?,
+        ),
+    };
+    Ok(scope)
+}
+
+/// The statistics of a deck or collection, or of every imported deck. See `scope`.
+fn stats(
+    deck: Option<&Path>,
+    args: &cli::Args,
+    config: &cli::Config,
+) -> io::Result<(String, Stats)> {
+    let reviews = ReviewLog::new(config.review_log()).load()?;
+    let schedule = Schedule::from_reviews(&reviews);
+    let now = Local::now();
+    let (title, files) = scope(deck, config)?;
+
+    let mut names = HashSet::new();
+    let mut cards = HashMap::new();
+    for path in &files {
+        let deck: Deck<Entry> = FileType::from_path(path).load()?;
+        let name = deck_name(path);
+        let due = schedule.due(&name, deal(deck, 0).into_cards(), args.direction, true, now);
+        for due in due {
+            cards.insert(due.key(), due.card.front().to_string());
+        }
+        names.insert(name);
+    }
+    // reviews of decks which are gone still count towards every deck
+    let reviews: Vec<_> = match deck {
+        Some(_) => reviews
+            .into_iter()
+            .filter(|review| names.contains(&review.deck))
+            .collect(),
+        None => reviews,
+    };
+    Ok((title, Stats::new(&reviews, &cards, now)))
+}
+
+/// Every review since the date joined with its deck and card, see `scope`.
+/// Reviews of decks which are gone are only kept when exporting every deck.
+fn export(
+    deck: Option<&Path>,
+    since: Option<NaiveDate>,
+    config: &cli::Config,
+) -> io::Result<Vec<Record>> {
+    let (_, files) = scope(deck, config)?;
+    let mut decks = HashMap::new();
+    let mut cards = HashMap::new();
+    for path in &files {
+        let deck: Deck<Entry> = FileType::from_path(path).load()?;
+        let name = deck_name(path);
+        let deck = deal(deck, 0);
+        let tags = deck.tags().to_vec();
+        decks.insert(name.clone(), deck.title().to_string());
+        for card in deck.into_cards() {
+            let tags: Vec<&str> = tags.iter().chain(card.tags()).map(String::as_str).collect();
+            let tags = tags.join(" ");
+            cards.insert((name.clone(), card.id()), (card, tags));
+        }
+    }
+
+    let mut records = vec![];
+    for review in ReviewLog::new(config.review_log()).load()? {
+        if since.is_some_and(|since| review.reviewed_at.date_naive() < since) {
+            continue;
+        }
+        let Some(title) = decks.get(&review.deck).cloned().or_else(|| {
+            // every deck was asked for, including the ones which are gone
+            deck.is_none().then(String::new)
+        }) else {
+            continue;
+        };
+        let card = cards.get(&(review.deck.clone(), review.card.clone()));
+        records.push(Record {
+            front: card
+                .map(|(card, _)| card.front().to_string())
+                .unwrap_or_default(),
+            back: card
+                .map(|(card, _)| card.back().to_string())
+                .unwrap_or_default(),
+            tags: card.map(|(_, tags)| tags.clone()).unwrap_or_default(),
+            deck_title: title,
+            reviewed_at: review.reviewed_at,
+            deck: review.deck,
+            card: review.card,
+            direction: review.direction,
+            score: review.score,
+            hinted: review.hinted,
+            answer_ms: review.answer_ms,
+        });
+    }
+    Ok(records)
+}
+
+/// Imports every deck of the sources into the collection, printing what became of each.
+/// Fails once they have all been tried if any of them couldn't be imported.
+fn import(
+    sources: &[PathBuf],
+    into: Option<&str>,
+    conflict: OnConflict,
+    config: &cli::Config,
+) -> error::Result<()> {
+    let mut collection = config.import_directory();
+    for part in into.into_iter().flat_map(|into| into.split(IFS)) {
+        collection.push(part);
+    }
+    let (mut imported, mut skipped, mut failed) = (0, 0, 0);
+    for source in sources {
+        let files = match import_sources(source) {
+            Ok(files) => files,
+            Err(e) => {
+                println!("failed    {}", e);
+                failed += 1;
+                continue;
             }
-            Subcommand::Run { name } => {
-                let mut filepath = config.import_directory();
-                name.split(IFS).for_each(|path| {
-                    filepath.push(path);
-                });
-                filepath.set_extension("json");
-                subcommand = Subcommand::Source { path: filepath };
-            }
-            Subcommand::Source { path } => {
-                let deck: Deck<Flashcard> = FileType::json(&path).load()?;
-                let mut flashcard_app = FlashcardApp::from(deck);
-                let action = app.run(&mut flashcard_app)?;
-
-                if let Exit::Quit = action {
-                    break;
+        };
+        if files.is_empty() {
+            println!("skipped   {}, no decks found", source.display());
+        }
+        for (file, relative) in files {
+            let destination = collection.join(&relative);
+            let shown = destination
+                .strip_prefix(config.import_directory())
+                .unwrap_or(&destination);
+            match import_deck(&file, &destination, conflict) {
+                Ok(Imported::Added) => println!("imported  {}", shown.display()),
+                Ok(Imported::Overwritten) => println!("replaced  {}", shown.display()),
+                Ok(Imported::Renamed(path)) => {
+                    let path = path
+                        .strip_prefix(config.import_directory())
+                        .unwrap_or(&path);
+                    println!("imported  {} as {}", shown.display(), path.display())
                 }
+                Ok(Imported::Merged(added)) => {
+                    println!("merged    {} new entries into {}", added, shown.display())
+                }
+                Ok(Imported::Skipped) => {
+                    println!("skipped   {}, already imported", shown.display());
+                    skipped += 1;
+                    continue;
+                }
+                Err(e) => {
+                    println!("failed    {}: {}", file.display(), e);
+                    failed += 1;
+                    continue;
+                }
+            }
+            imported += 1;
+        }
+    }
+    println!(
+        "{} imported, {} skipped, {} failed",
+        imported, skipped, failed
+    );
+    if failed > 0 {
+        return Err(Error::Import(failed));
+    }
+    Ok(())
+}
 
-                if let Exit::Drop = action {
-                    subcommand = Subcommand::Browse
+/// Merges the decks into the output, leaving out duplicates when `dedupe` is set,
+/// and moves the reviews of the cards kept over to it.
+/// Reviews of cards left out of the output itself are set aside under `<output>#dropped`.
+fn merge(
+    paths: &[PathBuf],
+    output: &Path,
+    dedupe: bool,
+    threshold: f64,
+    keep: Keep,
+    config: &cli::Config,
+) -> error::Result<()> {
+    if output.exists()
+        && !paths
+            .iter()
+            .any(|path| deck_name(path) == deck_name(output))
+    {
+        return Err(Error::Exists(output.to_path_buf()));
+    }
+    let mut decks: Vec<Deck<Entry>> = vec![];
+    for path in paths {
+        decks.push(FileType::detect(path)?.load()?);
+    }
+    let names: Vec<String> = paths.iter().map(|path| deck_name(path)).collect();
+    let log = ReviewLog::new(config.review_log());
+    let reviews = log.load()?;
+    let ids = |(d, e): Place| -> Vec<String> {
+        let deck = &decks[d];
+        deck.expand_entry(deck.cards()[e])
+            .iter()
+            .map(|card| card.id())
+            .collect()
+    };
+
+    let mut dropped = HashSet::new();
+    let found = if dedupe {
+        duplicates(&decks, threshold)
+    } else {
+        vec![]
+    };
+    let mut input = io::stdin().lock();
+    for duplicate in found {
+        let (first, second) = (duplicate.first, duplicate.second);
+        if dropped.contains(&first) || dropped.contains(&second) {
+            continue;
+        }
+        let drop = match keep {
+            Keep::First => Some(second),
+            Keep::Last => Some(first),
+            Keep::Reviewed => {
+                let count = |place: Place| {
+                    let ids = ids(place);
+                    reviews
+                        .iter()
+                        .filter(|review| {
+                            review.deck == names[place.0] && ids.contains(&review.card)
+                        })
+                        .count()
+                };
+                if count(second) > count(first) {
+                    Some(first)
+                } else {
+                    Some(second)
                 }
             }
-            Subcommand::Import { path } => {
-                if !path.is_file() {
-                    eprintln!("path is not a file");
-                    return Ok(());
+            Keep::Ask => {
+                let show = |(d, e): Place| {
+                    let deck = &decks[d];
+                    let cards = deck.expand_entry(deck.cards()[e]);
+                    let card = cards.first();
+                    format!(
+                        "{} card {}: {} / {}",
+                        paths[d].display(),
+                        e + 1,
+                        card.map(|card| card.front().as_str()).unwrap_or_default(),
+                        card.map(|card| card.back().as_str()).unwrap_or_default(),
+                    )
+                };
+                println!();
+                println!("{:.0}% alike", duplicate.similarity * 100.0);
+                println!("1) {}", show(first));
+                println!("2) {}", show(second));
+                print!("keep 1, 2 or b for both [1]> ");
+                io::stdout().flush()?;
+                let mut line = String::new();
+                input.read_line(&mut line)?;
+                match line.trim() {
+                    "2" => Some(first),
+                    "b" => None,
+                    _ => Some(second),
                 }
+            }
+        };
+        dropped.extend(drop);
+    }
 
-                let Some(filename) = path.file_name() else {
-                    eprintln!("is a file but has no name?");
-                    return Ok(());
-                };
-                let mut import_path = config.import_directory();
-                import_path.push(filename);
-                convert_to_path::<Deck<Flashcard>>(
-                    FileType::json(&path),
-                    FileType::json(&import_path),
-                )?;
-                // TODO some sort of file detection here.
-            }
-
-            Subcommand::Show => {
-                let imports_directory = config.import_directory();
-                let entries = list_directory(&imports_directory)?;
-                for entry in entries {
-                    match entry {
-                        ImportEntry::Collection(path) => {
-                            println!("{}/", path.file_stem().unwrap().to_string_lossy());
-                        }
-                        ImportEntry::File(path) => {
-                            println!("{}", path.file_stem().unwrap().to_string_lossy());
-                        }
+    let mut entries = vec![];
+    let mut moved = HashSet::new();
+    for (d, deck) in decks.iter().enumerate() {
+        for (e, entry) in deck.cards().into_iter().enumerate() {
+            if dropped.contains(&(d, e)) {
+                continue;
+            }
+            entries.push(entry.clone());
+            moved.extend(ids((d, e)).into_iter().map(|id| (names[d].clone(), id)));
+        }
+    }
+    let kept = entries.len();
+    let merged = decks[0].clone().with_cards(entries);
+    FileType::detect(output)?.save(&merged)?;
+
+    // cards left out of a deck which is also the output would otherwise
+    // leave their history next to that of the card kept in their place
+    let name = deck_name(output);
+    let left_out: HashSet<(String, String)> = dropped
+        .iter()
+        .filter(|(d, _)| names[*d] == name)
+        .flat_map(|&place| ids(place))
+        .map(|id| (name.clone(), id))
+        .filter(|card| !moved.contains(card))
+        .collect();
+    let aside = format!("{}#dropped", name);
+    let set_aside = log.relocate(|review| {
+        left_out
+            .contains(&(review.deck.clone(), review.card.clone()))
+            .then(|| aside.clone())
+    })?;
+    let count = log.relocate(|review| {
+        moved
+            .contains(&(review.deck.clone(), review.card.clone()))
+            .then(|| name.clone())
+    })?;
+    println!(
+        "{} cards kept, {} duplicates left out, {} reviews moved to {}",
+        kept,
+        dropped.len(),
+        count,
+        output.display()
+    );
+    if set_aside > 0 {
+        println!(
+            "{} reviews of left out cards set aside under {}",
+            set_aside, aside
+        );
+    }
+    Ok(())
+}
+
+/// A path as given when it exists, an imported name otherwise.
+fn resolve(path: &Path, config: &cli::Config) -> PathBuf {
+    if path.exists() {
+        path.to_path_buf()
+    } else {
+        import_path(config, &path.to_string_lossy())
+    }
+}
+
+/// An imported deck or collection by its name, or by a path to it within the import directory.
+/// Nothing outside of it is touched, whatever the working directory holds.
+fn imported(name: &Path, config: &cli::Config) -> error::Result<PathBuf> {
+    let path = import_path(config, &name.to_string_lossy());
+    if path.exists() {
+        return Ok(path);
+    }
+    let imports = fs::canonicalize(config.import_directory())
+        .map_err(Error::io(&config.import_directory()))?;
+    match fs::canonicalize(name) {
+        Ok(name) if name.starts_with(&imports) => Ok(name),
+        _ => Err(Error::io(&path)(io::ErrorKind::NotFound.into())),
+    }
+}
+
+/// Writes the pieces of a deck into a new collection in place of the deck,
+/// the reviews of each card go along with it.
+fn split(
+    path: &Path,
+    by: SplitBy,
+    output: Option<&Path>,
+    config: &cli::Config,
+) -> error::Result<()> {
+    let path = &imported(path, config)?;
+    let directory = output.map_or(path.with_extension(""), Path::to_path_buf);
+    if directory.exists() {
+        return Err(Error::Exists(directory));
+    }
+    let source = FileType::detect(path)?;
+    let extension = source.format();
+    let deck: Deck<Entry> = source.load()?;
+    let name = deck_name(path);
+
+    fs::create_dir_all(&directory)?;
+    let mut moved = HashMap::new();
+    for (piece, part) in deck.split(by) {
+        let file = directory.join(format!("{}.{}", piece, extension));
+        FileType::from_path(&file).save(&part)?;
+        let part_name = deck_name(&file);
+        for entry in part.cards() {
+            for card in part.expand_entry(entry) {
+                moved.insert(card.id(), part_name.clone());
+            }
+        }
+        println!("{:>5} entries  {}", part.cards().len(), file.display());
+    }
+    fs::remove_file(path)?;
+
+    let log = ReviewLog::new(config.review_log());
+    let count = log.relocate(|review| {
+        (review.deck == name)
+            .then(|| moved.get(&review.card).cloned())
+            .flatten()
+    })?;
+    println!("{} reviews moved to {}", count, directory.display());
+    Ok(())
+}
+
+/// Moves a deck or collection within the import directory and points its reviews at
+/// the new place. Both are imported names, `from` may also be a path within the imports.
+fn move_deck(from: &Path, to: &Path, config: &cli::Config) -> error::Result<()> {
+    let from = imported(from, config)?;
+    let mut to = config.import_directory().join(to);
+    if to.is_dir() {
+        to.push(from.file_name().unwrap_or_default());
+    } else if from.is_file() && to.extension().is_none() {
+        to.set_extension(from.extension().unwrap_or_default());
+    }
+    if to.exists() {
+        return Err(Error::Exists(to));
+    }
+
+    // names are worked out before the move, they can't be once the files are gone
+    let names: Vec<(String, PathBuf)> = if from.is_dir() {
+        deck_paths(&from)?
+            .iter()
+            .map(|file| {
+                let relative = file.strip_prefix(&from).unwrap_or(file);
+                (deck_name(file), to.join(relative))
+            })
+            .collect()
+    } else {
+        vec![(deck_name(&from), to.clone())]
+    };
+    if let Some(parent) = to.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::rename(&from, &to)?;
+
+    let renamed: HashMap<String, String> = names
+        .into_iter()
+        .map(|(name, path)| (name, deck_name(&path)))
+        .collect();
+    let log = ReviewLog::new(config.review_log());
+    let count = log.relocate(|review| renamed.get(&review.deck).cloned())?;
+    let shown = |path: &Path| {
+        path.strip_prefix(config.import_directory())
+            .unwrap_or(path)
+            .display()
+            .to_string()
+    };
+    println!(
+        "moved {} to {}, {} reviews moved along",
+        shown(&from),
+        shown(&to),
+        count
+    );
+    Ok(())
+}
+
+fn main() {
+    if let Err(error) = run() {
+        eprintln!("kadeu: {}", Error::from(error));
+        std::process::exit(1);
+    }
+}
+
+fn run() -> io::Result<()> {
+    let args = cli::Args::parse();
+    let subcommand = args.subcommand.clone().unwrap_or_default();
+    let config = args.read_config()?;
+    let seed = args.seed.unwrap_or_else(rand::random);
+    // lines a crash left half written are read around, saying what was lost
+    let skipped = ReviewLog::new(config.review_log()).read()?.skipped;
+    for error in &skipped {
+        eprintln!("kadeu: skipped {}", error);
+    }
+
+    // reports printed as text are done before the terminal is taken over,
+    // everything else names the screens to start on
+    let screens = match subcommand {
+        Subcommand::Stats {
+            command:
+                Some(StatsCommand::Export {
+                    deck,
+                    format,
+                    since,
+                    output,
+                }),
+            ..
+        } => {
+            let records = export(deck.as_deref(), since, &config)?;
+            match output {
+                Some(path) => write_records(&records, format, fs::File::create(path)?)?,
+                None => write_records(&records, format, stdout())?,
+            }
+            return Ok(());
+        }
+        Subcommand::Stats {
+            deck,
+            format: Format::Text,
+            ..
+        } => {
+            print!("{}", stats(deck.as_deref(), &args, &config)?.1);
+            return Ok(());
+        }
+        Subcommand::Stats {
+            deck,
+            format: Format::Json,
+            ..
+        } => {
+            let (_, stats) = stats(deck.as_deref(), &args, &config)?;
+            println!("{}", serde_json::to_string_pretty(&stats)?);
+            return Ok(());
+        }
+        Subcommand::Stats {
+            deck,
+            format: Format::Tui,
+            ..
+        } => vec![Screen::Stats(deck)],
+        Subcommand::Import {
+            paths,
+            into,
+            on_conflict,
+        } => return Ok(import(&paths, into.as_deref(), on_conflict, &config)?),
+        Subcommand::Check { paths } => {
+            let mut problems = 0;
+            let mut files = vec![];
+            for path in paths {
+                if path.is_dir() {
+                    files.extend(deck_paths(&path)?);
+                } else {
+                    files.push(path);
+                }
+            }
+            for file in &files {
+                for problem in check(file)? {
+                    // positions read as path:line:column
+                    match problem.location {
+                        Location::Position { .. } => println!("{}:{}", file.display(), problem),
+                        _ => println!("{}: {}", file.display(), problem),
                     }
+                    problems += 1;
                 }
             }
-            _ => {}
+            println!("{} problems in {} decks", problems, files.len());
+            if problems > 0 {
+                std::process::exit(1);
+            }
+            return Ok(());
+        }
+        Subcommand::Merge {
+            decks,
+            output,
+            dedupe,
+            similarity,
+            keep,
+        } => return Ok(merge(&decks, &output, dedupe, similarity, keep, &config)?),
+        Subcommand::Split { deck, by, output } => {
+            return Ok(split(&deck, by, output.as_deref(), &config)?)
+        }
+        Subcommand::Mv { from, to } => return Ok(move_deck(&from, &to, &config)?),
+        Subcommand::Show { tree, long, json } => {
+            let schedule = Schedule::from_reviews(&ReviewLog::new(config.review_log()).load()?);
+            let listings = listing(&config.import_directory(), tree, &schedule, &args, &config)?;
+            if json {
+                println!("{}", serde_json::to_string_pretty(&listings)?);
+            } else {
+                write_listing(&listings, long, 0, &mut stdout())?;
+            }
+            return Ok(());
+        }
+        Subcommand::Config => {
+            print!(
+                "{}",
+                toml::to_string_pretty(&config).map_err(io::Error::other)?
+            );
+            return Ok(());
+        }
+        Subcommand::Run { names, plain: true } => {
+            let paths: Vec<PathBuf> = names
+                .iter()
+                .map(|name| import_path(&config, name))
+                .collect();
+            let mut flashcard_app = session(&paths, None, None, &args, &config, seed)?;
+            return flashcard_app.run_plain(io::stdin().lock(), stdout());
+        }
+        Subcommand::Run {
+            names,
+            plain: false,
+        } => {
+            let paths = names
+                .iter()
+                .map(|name| import_path(&config, name))
+                .collect();
+            vec![Screen::Study(paths)]
+        }
+        Subcommand::Source { paths } => vec![Screen::Study(paths)],
+        Subcommand::Study { query, save } => {
+            // a query that doesn't parse is reported on its screen rather than saved
+            if let (Some(name), Ok(_)) = (&save, Query::parse(&query)) {
+                let mut path = config.import_directory();
+                path.push(name);
+                path.set_extension(QUERY_EXTENSION);
+                fs::write(path, &query)?;
+            }
+            vec![Screen::Query { query, name: save }]
         }
+        // back to where the deck was picked from once it is edited
+        Subcommand::Edit { path } => vec![Screen::Browse, Screen::Edit { path, focus: None }],
+        Subcommand::Browse => vec![Screen::Browse],
+        Subcommand::Home => vec![],
+    };
+
+    // only the screens take over the terminal, until the guard is dropped
+    let _guard = TerminalGuard::enter()?;
+    let mut app = AppHandler::from(crossterm_terminal()?);
+    // every screen leads back to the home screen in the end
+    let mut screens = [vec![Screen::Home], screens].concat();
+    if !skipped.is_empty() {
+        let lines: Vec<String> = skipped.iter().map(ToString::to_string).collect();
+        screens.push(Screen::Message {
+            title: "Review log".to_string(),
+            message: format!(
+                "{} of its lines couldn't be read and were skipped\n{}",
+                skipped.len(),
+                lines.join("\n")
+            ),
+        });
     }
-    disable_raw_mode()?;
+    app.run(screens, |screen| open(screen, &args, &config, seed))
+}
 
-    Ok(())
+/// Makes the app of a screen asked for.
+fn open(
+    screen: &Screen,
+    args: &cli::Args,
+    config: &cli::Config,
+    seed: u64,
+) -> io::Result<Box<dyn KadeuApp>> {
+    let app: Box<dyn KadeuApp> = match screen {
+        Screen::Home => Box::new(Home::new(summaries(args, config)?)),
+        Screen::Browse => {
+            let imports = config.import_directory();
+            Box::new(DeckBrowser::try_from(imports.clone()).map_err(Error::io(&imports))?)
+        }
+        Screen::Study(paths) => Box::new(session(paths, None, None, args, config, seed)?),
+        Screen::Query { query, name } => {
+            let parsed = Query::parse(query)?;
+            let paths = vec![config.import_directory()];
+            let title = name.as_deref().unwrap_or(query.as_str());
+            Box::new(session(
+                &paths,
+                Some(title),
+                Some(&parsed),
+                args,
+                config,
+                seed,
+            )?)
+        }
+        Screen::Edit { path, focus } => {
+            let mut editor = DeckEditor::open(path)?;
+            if let Some(front) = focus {
+                editor.focus(front);
+            }
+            Box::new(editor)
+        }
+        Screen::Create(directory) => Box::new(DeckEditor::create(directory)),
+        Screen::Message { title, message } => Box::new(Dialog::new(title, message)),
+        Screen::Stats(deck) => {
+            let (title, stats) = stats(deck.as_deref(), args, config)?;
+            Box::new(StatsApp::new(&title, stats).with_daily_goal(config.daily_goal()))
+        }
+    };
+    Ok(app)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::path::{Path, PathBuf};
+
+    use chrono::{Duration, Local};
+    use clap::Parser;
+    use kadeu::cli::{Args, Config, ExportFormat, Keep, OnConflict};
+    use kadeu::game::review::{Review, ReviewLog};
+    use kadeu::game::schedule::Schedule;
+    use kadeu::game::{fingerprint, Direction, DirectionMode, Score};
+    use kadeu::io::{deck_name, write_records, FileType};
+    use kadeu::{app::Deck, Entry};
+
+    use super::{export, import, listing, merge, move_deck, write_listing};
+
+    /// A configuration with the imports and the review log in a directory of their own.
+    fn scratch(name: &str) -> (PathBuf, Config) {
+        let directory = std::env::temp_dir().join(format!("kadeu-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&directory);
+        fs::create_dir_all(directory.join("imports")).unwrap();
+        let config = toml::from_str(&format!(
+            "import_directory = {:?}\nreview_log = {:?}\ndefault_strategy = \"Linear\"",
+            directory.join("imports"),
+            directory.join("reviews.jsonl")
+        ))
+        .unwrap();
+        (directory, config)
+    }
+
+    fn review(deck: &Path, card: &str) -> Review {
+        Review {
+            deck: deck_name(deck),
+            card: card.to_string(),
+            direction: Direction::Forward,
+            score: Score::Hit,
+            hinted: false,
+            answer_ms: None,
+            reviewed_at: Local::now(),
+        }
+    }
+
+    #[test]
+    fn session_options_follow_the_subcommand() {
+        let args = Args::try_parse_from([
+            "kadeu",
+            "run",
+            "d",
+            "--direction",
+            "both",
+            "--all",
+            "--tag",
+            "verbs",
+            "--plain",
+        ])
+        .unwrap();
+        assert_eq!(args.direction, DirectionMode::Both);
+        assert!(args.all);
+        assert_eq!(args.tags, vec!["verbs"]);
+    }
+
+    #[test]
+    fn imports_go_into_their_collection() {
+        let (directory, config) = scratch("import");
+        let src = directory.join("src");
+        fs::create_dir_all(src.join("net")).unwrap();
+        fs::write(
+            src.join("net/d.json"),
+            r#"{"title": "d", "cards": [{"front": "dns", "back": "53"}]}"#,
+        )
+        .unwrap();
+        fs::write(src.join("net/README.md"), "# decks").unwrap();
+
+        // files of the glob which aren't decks are passed over rather than failing
+        import(
+            &[src.join("*/*")],
+            Some("lang/old"),
+            OnConflict::Skip,
+            &config,
+        )
+        .unwrap();
+        let imports = config.import_directory();
+        assert!(imports.join("lang/old/net/d.json").is_file());
+        assert!(!imports.join("lang/old/net/README.md").exists());
+        import(std::slice::from_ref(&src), None, OnConflict::Skip, &config).unwrap();
+        assert!(imports.join("src/net/d.json").is_file());
+        fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[test]
+    fn exports_join_reviews_with_their_cards() {
+        let (directory, config) = scratch("export");
+        let deck = config.import_directory().join("animals.json");
+        fs::write(
+            &deck,
+            r#"{"title": "Animals", "tags": ["german"], "cards": [{"front": "hund", "back": "dog", "tags": ["pets"]}]}"#,
+        )
+        .unwrap();
+        let log = ReviewLog::new(config.review_log());
+        let hund = fingerprint("hund");
+        log.append(&Review {
+            reviewed_at: Local::now() - Duration::days(30),
+            ..review(&deck, &hund)
+        })
+        .unwrap();
+        log.append(&review(&deck, &hund)).unwrap();
+        log.append(&review(&deck, "gone")).unwrap();
+
+        let name = deck_name(&deck);
+        let since = (Local::now() - Duration::days(7)).date_naive();
+        let records = export(None, Some(since), &config).unwrap();
+        let mut csv = vec![];
+        write_records(&records, ExportFormat::Csv, &mut csv).unwrap();
+        // the time of each review is left out, it changes from run to run
+        let rows: Vec<String> = String::from_utf8(csv)
+            .unwrap()
+            .lines()
+            .map(|row| row.split_once(',').unwrap().1.to_string())
+            .collect();
+        assert_eq!(
+            rows,
+            vec![
+                "deck,deck_title,card,front,back,tags,direction,score,hinted,answer_ms".to_string(),
+                format!(
+                    "{},Animals,{},hund,dog,german pets,forward,hit,false,",
+                    name, hund
+                ),
+                format!("{},Animals,gone,,,,forward,hit,false,", name),
+            ]
+        );
+
+        let mut json = vec![];
+        write_records(&records, ExportFormat::Json, &mut json).unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&json).unwrap();
+        assert_eq!(json.as_array().unwrap().len(), 2);
+        assert_eq!(json[0]["front"], "hund");
+        assert_eq!(json[0]["tags"], "german pets");
+        assert_eq!(json[1]["card"], "gone");
+        assert_eq!(json[1]["front"], "");
+        fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[test]
+    fn merges_keep_the_history_of_the_card_kept() {
+        let (directory, config) = scratch("merge");
+        let imports = config.import_directory();
+        let (a, b) = (imports.join("a.json"), imports.join("b.json"));
+        fs::write(
+            &a,
+            r#"{"title": "a", "cards": [{"front": "DNS", "back": "53"}]}"#,
+        )
+        .unwrap();
+        fs::write(
+            &b,
+            r#"{"title": "b", "cards": [{"front": "DNS", "back": "53"}, {"front": "SSH", "back": "22"}]}"#,
+        )
+        .unwrap();
+        let log = ReviewLog::new(config.review_log());
+        let dns = fingerprint("DNS");
+        log.append(&review(&a, &dns)).unwrap();
+        log.append(&review(&b, &dns)).unwrap();
+        log.append(&review(&b, &dns)).unwrap();
+
+        // the card of b has more reviews, so the one already in the output is left out
+        merge(
+            &[a.clone(), b.clone()],
+            &a,
+            true,
+            0.9,
+            Keep::Reviewed,
+            &config,
+        )
+        .unwrap();
+        let deck: Deck<Entry> = FileType::from_path(&a).load().unwrap();
+        assert_eq!(deck.cards().len(), 2);
+        let reviews = log.load().unwrap();
+        let under = |deck: &str| reviews.iter().filter(|review| review.deck == deck).count();
+        assert_eq!(under(&deck_name(&a)), 2);
+        assert_eq!(under(&format!("{}#dropped", deck_name(&a))), 1);
+        assert_eq!(under(&deck_name(&b)), 0);
+        fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[test]
+    fn moved_decks_keep_their_reviews() {
+        let (directory, config) = scratch("mv");
+        let imports = config.import_directory();
+        let deck = imports.join("german/animals.json");
+        fs::create_dir_all(imports.join("german")).unwrap();
+        fs::write(
+            &deck,
+            r#"{"title": "animals", "cards": [{"front": "hund", "back": "dog"}]}"#,
+        )
+        .unwrap();
+        let log = ReviewLog::new(config.review_log());
+        log.append(&review(&deck, "hund")).unwrap();
+        log.append(&review(&imports.join("french.json"), "chien"))
+            .unwrap();
+
+        // a directory of the same name outside of the imports isn't taken for the collection
+        fs::create_dir_all(directory.join("german")).unwrap();
+        assert!(move_deck(&directory.join("german"), Path::new("elsewhere"), &config).is_err());
+        assert!(directory.join("german").exists());
+
+        move_deck(Path::new("german"), Path::new("languages/german"), &config).unwrap();
+        let moved = imports.join("languages/german/animals.json");
+        assert!(moved.exists());
+        let decks: Vec<String> = log.load().unwrap().into_iter().map(|r| r.deck).collect();
+        assert_eq!(
+            decks,
+            vec![deck_name(&moved), deck_name(&imports.join("french.json"))]
+        );
+        fs::remove_dir_all(&directory).unwrap();
+    }
+
+    // names which aren't valid unicode can only be made on unix
+    #[cfg(unix)]
+    #[test]
+    fn listings_for_scripts() {
+        use std::ffi::OsStr;
+        use std::os::unix::ffi::OsStrExt;
+
+        let (directory, config) = scratch("show");
+        let imports = config.import_directory();
+        fs::create_dir_all(imports.join("german")).unwrap();
+        let deck = r#"{"title": "Animals", "cards": [{"front": "hund", "back": "dog"}]}"#;
+        fs::write(imports.join("german/animals.json"), deck).unwrap();
+        fs::write(imports.join("broken.json"), "{").unwrap();
+        fs::write(imports.join("odd.name.txt"), "").unwrap();
+        fs::write(imports.join(OsStr::from_bytes(b"caf\xe9.json")), "").unwrap();
+
+        let args = Args::try_parse_from(["kadeu"]).unwrap();
+        let schedule = Schedule::from_reviews(&[]);
+        let listings = listing(&imports, true, &schedule, &args, &config).unwrap();
+        let mut text = vec![];
+        write_listing(&listings, false, 0, &mut text).unwrap();
+        assert_eq!(
+            String::from_utf8(text).unwrap(),
+            "broken\ncaf\u{fffd}\ngerman/\n  animals\nodd.name\n"
+        );
+        let mut long = vec![];
+        write_listing(&listings, true, 0, &mut long).unwrap();
+        let long = String::from_utf8(long).unwrap();
+        assert!(long.contains("Animals"));
+        assert!(long.contains("1 cards  json  1 new  0 learning  0 due"));
+
+        let json = serde_json::to_value(&listings).unwrap();
+        let kinds: Vec<&str> = json
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|listing| listing["kind"].as_str().unwrap())
+            .collect();
+        assert_eq!(kinds, vec!["file", "file", "collection", "file"]);
+        let animals = &json[2]["entries"][0];
+        assert_eq!(animals["name"], "animals");
+        assert_eq!(animals["kind"], "deck");
+        assert_eq!(animals["cards"], 1);
+        assert_eq!(animals["due"]["new"], 1);
+        // files which aren't decks are listed without any of what a deck has
+        assert!(json[0].get("title").is_none());
+        fs::remove_dir_all(&directory).unwrap();
+    }
 }
@@ -0,0 +1,184 @@
+use crate::app::Deck;
+use crate::game::Kadeu;
+use crate::Entry;
+
+/// An entry of one of the decks being merged, by the index of its deck and its index in the deck.
+pub type Place = (usize, usize);
+
+/// Two entries which make the same cards, or nearly.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Duplicate {
+    /// The entry that comes first in the order the decks were given.
+    pub first: Place,
+    pub second: Place,
+    /// From 0 for nothing in common to 1 for the same text once normalized.
+    pub similarity: f64,
+}
+
+/// Lowercases the text and keeps only its words, separated by single spaces.
+pub fn normalize(text: &str) -> String {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// How alike two texts are, by the share of characters that don't have to be edited
+/// to turn one into the other.
+pub fn similarity(a: &str, b: &str) -> f64 {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let longest = a.len().max(b.len());
+    if longest == 0 {
+        return 1.0;
+    }
+    // edit distance, keeping a single row of the table
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, x) in a.iter().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, y) in b.iter().enumerate() {
+            let substitution = diagonal + usize::from(x != y);
+            diagonal = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(diagonal + 1);
+        }
+    }
+    1.0 - row[b.len()] as f64 / longest as f64
+}
+
+/// The text an entry is compared by, the fronts and the backs of the cards it makes apart
+/// so that the same question with another answer isn't taken for a duplicate.
+pub fn entry_faces(deck: &Deck<Entry>, entry: &Entry) -> (String, String) {
+    let cards = deck.expand_entry(entry);
+    let fronts: Vec<String> = cards.iter().map(|card| card.front().to_string()).collect();
+    let backs: Vec<String> = cards.iter().map(|card| card.back().to_string()).collect();
+    (normalize(&fronts.join(" ")), normalize(&backs.join(" ")))
+}
+
+// characters are counted in this many buckets to rule out pairs cheaply
+const BUCKETS: usize = 64;
+
+/// A normalized text with what is needed to rule it out without a full comparison.
+struct Face {
+    text: String,
+    chars: usize,
+    buckets: [u16; BUCKETS],
+}
+
+impl Face {
+    fn new(text: String) -> Self {
+        let mut buckets = [0u16; BUCKETS];
+        for c in text.chars() {
+            let bucket = &mut buckets[c as usize % BUCKETS];
+            *bucket = bucket.saturating_add(1);
+        }
+        Self {
+            chars: text.chars().count(),
+            text,
+            buckets,
+        }
+    }
+
+    /// How many edits the texts may be apart and still be `threshold` alike.
+    fn allowed(&self, other: &Face, threshold: f64) -> usize {
+        let longest = self.chars.max(other.chars) as f64;
+        ((1.0 - threshold) * longest + 1e-9).floor() as usize
+    }
+
+    /// The similarity of the texts when it is at least `threshold`.
+    fn matches(&self, other: &Face, threshold: f64) -> Option<f64> {
+        if self.text == other.text {
+            return Some(1.0);
+        }
+        let allowed = self.allowed(other, threshold);
+        if self.chars.abs_diff(other.chars) > allowed {
+            return None;
+        }
+        // every character one text has more of than the other takes an edit
+        let (mut more, mut fewer) = (0, 0);
+        for (a, b) in self.buckets.iter().zip(&other.buckets) {
+            if a > b {
+                more += usize::from(a - b);
+            } else {
+                fewer += usize::from(b - a);
+            }
+        }
+        if more.max(fewer) > allowed {
+            return None;
+        }
+        Some(similarity(&self.text, &other.text)).filter(|similarity| *similarity >= threshold)
+    }
+}
+
+/// Every pair of entries across the decks, or within one, whose fronts and backs are
+/// each at least `threshold` alike.
+pub fn duplicates(decks: &[Deck<Entry>], threshold: f64) -> Vec<Duplicate> {
+    let mut entries: Vec<(Place, Face, Face)> = vec![];
+    for (d, deck) in decks.iter().enumerate() {
+        for (e, entry) in deck.cards().into_iter().enumerate() {
+            let (front, back) = entry_faces(deck, entry);
+            entries.push(((d, e), Face::new(front), Face::new(back)));
+        }
+    }
+    // by the length of the front, the entries after one are only compared
+    // until their fronts are too long to be alike
+    entries.sort_by_key(|(_, front, _)| front.chars);
+    let mut duplicates = vec![];
+    for (i, (a, a_front, a_back)) in entries.iter().enumerate() {
+        for (b, b_front, b_back) in &entries[i + 1..] {
+            if b_front.chars - a_front.chars > a_front.allowed(b_front, threshold) {
+                break;
+            }
+            let Some(front) = a_front.matches(b_front, threshold) else {
+                continue;
+            };
+            let Some(back) = a_back.matches(b_back, threshold) else {
+                continue;
+            };
+            duplicates.push(Duplicate {
+                first: *a.min(b),
+                second: *a.max(b),
+                similarity: front.min(back),
+            });
+        }
+    }
+    duplicates.sort_by_key(|duplicate| (duplicate.first, duplicate.second));
+    duplicates
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{duplicates, normalize};
+    use crate::app::Deck;
+    use crate::{Entry, Flashcard};
+
+    #[test]
+    fn near_duplicates_across_decks() {
+        let deck = |cards: &[(&str, &str)]| {
+            let cards = cards
+                .iter()
+                .map(|(front, back)| {
+                    Entry::Flashcard(Flashcard::new(front.to_string(), back.to_string()))
+                })
+                .collect();
+            Deck::<Entry>::new("deck").with_cards(cards)
+        };
+        let a = deck(&[
+            ("What does SYN do?", "Opens a connection"),
+            ("Port of DNS", "53"),
+        ]);
+        let b = deck(&[
+            ("what does syn do", "opens a connection."),
+            ("What does FIN do?", "Closes a connection"),
+            ("Port of DNS", "35"),
+        ]);
+        assert_eq!(normalize("What does SYN do?"), "what does syn do");
+
+        // the same question with another answer is kept
+        let found = duplicates(&[a, b], 0.85);
+        let pairs: Vec<_> = found.iter().map(|d| (d.first, d.second)).collect();
+        assert_eq!(pairs, vec![((0, 0), (1, 0))]);
+        assert_eq!(found[0].similarity, 1.0);
+    }
+}
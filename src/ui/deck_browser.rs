@@ -1,28 +1,40 @@
 use crate::app::{Card, Deck};
-use crate::game::Kadeu;
+use crate::game::query::QUERY_EXTENSION;
+use crate::game::Expand;
 use crate::io::FileType;
 use crate::ui::inputs::Input;
+use crate::Entry;
 use crossterm::event::KeyCode;
-use ratatui::prelude::Backend;
 use ratatui::style::Color;
 use ratatui::text::Text;
 use ratatui::widgets::ListState;
-use ratatui::Terminal;
+use ratatui::Frame;
 use serde::de::DeserializeOwned;
+use std::collections::HashMap;
 use std::ffi::OsString;
 use std::fs;
 use std::mem::swap;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use super::inputs::KeyMap;
 use super::style::AppStyle;
-use super::{Exit, KadeuApp};
+use super::{Intent, KadeuApp, Outcome, Screen};
 
 pub struct DeckBrowser {
     root: PathBuf,
     relative_path: PathBuf,
     collection: FileCollection,
     index: usize,
+    search: Option<Search>,
+    // tags of the decks looked at by a search, read once per deck
+    tags: HashMap<PathBuf, Vec<String>>,
+}
+
+/// Narrows the listing down to entries whose name or tags contain the query.
+#[derive(Debug, Clone, Default)]
+struct Search {
+    query: String,
+    typing: bool,
 }
 
 impl TryFrom<PathBuf> for DeckBrowser {
@@ -34,6 +46,8 @@ impl TryFrom<PathBuf> for DeckBrowser {
             root,
             collection,
             index: 0,
+            search: None,
+            tags: HashMap::new(),
         };
 
         Ok(browser)
@@ -78,22 +92,28 @@ impl FileCollection {
         &self.root
     }
 
-    pub fn peek_index(&self) -> &PathBuf {
-        self.subpaths.get(self.index).unwrap()
+    /// The selected path, none when the directory is empty.
+    pub fn peek_index(&self) -> Option<&PathBuf> {
+        self.subpaths.get(self.index)
     }
 
     pub fn index_filename(&self) -> OsString {
-        self.peek_index().file_name().unwrap().to_os_string()
+        self.peek_index()
+            .and_then(|path| path.file_name())
+            .unwrap_or_default()
+            .to_os_string()
     }
 
     fn dec(&mut self) {
-        if self.index < self.subpaths.len() - 1 {
+        if self.index + 1 < self.subpaths.len() {
             self.index += 1;
         }
     }
 
     pub fn traverse(&mut self) -> std::io::Result<()> {
-        let path = self.peek_index().clone();
+        let Some(path) = self.peek_index().cloned() else {
+            return Ok(());
+        };
         if path.is_dir() {
             let mut collection = FileCollection::try_from(path)?;
             swap(self, &mut collection);
@@ -102,6 +122,14 @@ impl FileCollection {
         Ok(())
     }
 
+    pub fn refresh(&mut self) -> std::io::Result<()> {
+        let index = self.index;
+        let mut collection = FileCollection::try_from(self.root.clone())?;
+        swap(self, &mut collection);
+        self.index = index.min(self.subpaths.len().saturating_sub(1));
+        Ok(())
+    }
+
     pub fn reverse(&mut self) -> std::io::Result<()> {
         if let Some(parent) = self.root().parent() {
             let path = parent.to_path_buf();
@@ -112,70 +140,120 @@ impl FileCollection {
         Ok(())
     }
 
+    /// Re-reads the directory keeping only the paths the filter accepts.
+    fn filter(&mut self, mut keep: impl FnMut(&PathBuf) -> bool) -> std::io::Result<()> {
+        self.refresh()?;
+        self.subpaths.retain(|path| keep(path));
+        self.index = 0;
+        Ok(())
+    }
+
     fn view(&self) -> Vec<String> {
-        // all subpaths are filtered to be some at this point.
         self.subpaths
             .iter()
-            .map(|path| path.file_name().unwrap().to_string_lossy().to_string())
+            .map(|path| {
+                path.file_name()
+                    .unwrap_or_default()
+                    .to_string_lossy()
+                    .to_string()
+            })
             .collect()
     }
 }
 
 impl KadeuApp for DeckBrowser {
-    fn handle_input(&mut self, input: Option<&Input>) -> std::io::Result<Exit> {
+    fn handle_input(&mut self, input: Option<&Input>) -> std::io::Result<Intent> {
         let Some(input) = input else {
-            return Ok(Exit::None);
+            return Ok(Intent::None);
         };
-        let exit = match input {
-            Input::Up => {
+        if self.search.as_ref().is_some_and(|search| search.typing) {
+            self.handle_search(input)?;
+            return Ok(Intent::None);
+        }
+        let selected = self.collection.peek_index().cloned();
+        let intent = match (input, selected) {
+            (Input::Key(KeyCode::Char('/')), _) => {
+                self.search = Some(Search {
+                    typing: true,
+                    ..self.search.take().unwrap_or_default()
+                });
+                Intent::None
+            }
+            (Input::Escape, _) if self.search.is_some() => {
+                self.search = None;
+                self.collection.refresh()?;
+                Intent::None
+            }
+            (Input::Up, _) => {
                 self.collection.inc();
-                Exit::None
+                Intent::None
             }
 
-            Input::Down => {
+            (Input::Down, _) => {
                 self.collection.dec();
-                Exit::None
+                Intent::None
             }
 
-            Input::Backspace => {
+            (Input::Backspace, _) => {
                 if self.collection.root() != &self.root {
+                    self.search = None;
                     self.relative_path.pop();
                     self.collection.reverse()?;
                 }
-                Exit::None
+                Intent::None
             }
-            Input::Select => {
-                if self.collection.peek_index().is_dir() {
-                    self.relative_path.push(self.collection.index_filename());
-                    self.collection.traverse()?;
-                    Exit::None
-                } else {
-                    Exit::Drop
-                }
+            (Input::Select, Some(path)) if path.is_dir() => {
+                self.search = None;
+                self.relative_path.push(self.collection.index_filename());
+                self.collection.traverse()?;
+                Intent::None
+            }
+            (Input::Select, Some(path))
+                if path.extension().is_some_and(|ext| ext == QUERY_EXTENSION) =>
+            {
+                let query = fs::read_to_string(&path)?.trim().to_string();
+                let name = path
+                    .file_stem()
+                    .map(|stem| stem.to_string_lossy().to_string());
+                Intent::Push(Screen::Query { query, name })
+            }
+            // studies a whole collection, where enter would open it
+            (Input::Select | Input::Key(KeyCode::Char('s')), Some(path)) => {
+                Intent::Push(Screen::Study(vec![path]))
+            }
+            (Input::Key(KeyCode::Char('e')), Some(path)) if path.is_file() => {
+                Intent::Push(Screen::Edit { path, focus: None })
+            }
+            (Input::Key(KeyCode::Char('n')), _) => {
+                Intent::Push(Screen::Create(self.current_directory()))
             }
-            _ => Exit::None,
+            (Input::Key(KeyCode::Char('h')), _) => Intent::Pop,
+            _ => Intent::None,
         };
-        let action = exit;
-        Ok(action)
-    }
-    fn render<B: Backend>(
-        &mut self,
-        terminal: &mut Terminal<B>,
-        style: &AppStyle,
-    ) -> std::io::Result<()> {
+        Ok(intent)
+    }
+
+    /// Decks may have been made or edited on the screens above.
+    fn resume(&mut self, _outcome: Option<Outcome>) -> std::io::Result<Intent> {
+        self.refresh()?;
+        Ok(Intent::None)
+    }
+
+    fn render(&mut self, frame: &mut Frame, style: &AppStyle) {
         let view = self.collection.view();
         let items = view.into_iter().map(|item| Text::from(item));
-        let title = self.relative_path.as_os_str().to_string_lossy().to_string();
+        let mut title = self.relative_path.as_os_str().to_string_lossy().to_string();
+        if let Some(search) = &self.search {
+            let cursor = if search.typing { "_" } else { "" };
+            title = format!("{} /{}{}", title, search.query, cursor);
+        }
         let list = style
             .list(items)
             .highlight_symbol("> ")
             .block(style.block().title(title));
         let mut state = ListState::default();
         state.select(Some(self.collection.index));
-        terminal.draw(|frame| {
-            frame.render_stateful_widget(list, frame.area(), &mut state);
-        })?;
-        Ok(())
+        frame.render_stateful_widget(list, frame.area(), &mut state);
     }
     fn style(&self) -> AppStyle {
         AppStyle::default().bg(Color::White)
@@ -183,6 +261,14 @@ impl KadeuApp for DeckBrowser {
 
     fn keymap(&self) -> KeyMap {
         let mut map = KeyMap::new();
+        if self.search.as_ref().is_some_and(|search| search.typing) {
+            // letters go into the query
+            map.insert(KeyCode::Enter, Input::Select);
+            map.insert(KeyCode::Backspace, Input::Backspace);
+            map.insert(KeyCode::Esc, Input::Escape);
+            return map;
+        }
+        map.insert(KeyCode::Esc, Input::Escape);
         map.insert(KeyCode::Char('q'), Input::Quit);
         map.insert(KeyCode::Char('j'), Input::Down);
         map.insert(KeyCode::Char('k'), Input::Up);
@@ -194,31 +280,86 @@ impl KadeuApp for DeckBrowser {
     fn drop(&mut self) -> std::io::Result<()> {
         Ok(())
     }
+
+    fn disable_universal_keymap(&self) -> bool {
+        self.search.as_ref().is_some_and(|search| search.typing)
+    }
 }
 
 impl DeckBrowser {
-    pub fn current_path_is_file(&self) -> bool {
-        self.collection.peek_index().is_file()
+    fn handle_search(&mut self, input: &Input) -> std::io::Result<()> {
+        let Some(search) = self.search.as_mut() else {
+            return Ok(());
+        };
+        match input {
+            Input::Key(KeyCode::Char(c)) => search.query.push(*c),
+            Input::Backspace => {
+                search.query.pop();
+            }
+            // keep the results and go back to moving through them
+            Input::Select => search.typing = false,
+            Input::Escape => self.search = None,
+            _ => return Ok(()),
+        }
+
+        let query = match &self.search {
+            Some(search) if !search.query.is_empty() => search.query.to_lowercase(),
+            _ => return self.collection.refresh(),
+        };
+        let tags = &mut self.tags;
+        self.collection.filter(|path| {
+            let name = path.file_name().unwrap_or_default().to_string_lossy();
+            if name.to_lowercase().contains(&query) {
+                return true;
+            }
+            tags.entry(path.clone())
+                .or_insert_with(|| deck_tags(path))
+                .iter()
+                .any(|tag| tag.to_lowercase().contains(&query))
+        })
     }
 
-    pub fn current_path(&self) -> PathBuf {
-        self.collection.peek_index().clone()
+    /// The directory currently being browsed.
+    pub fn current_directory(&self) -> PathBuf {
+        self.collection.root().clone()
     }
-    pub fn is_deck<T: Kadeu + DeserializeOwned>(&self) -> bool {
-        let file = self.collection.peek_index();
 
-        if file.is_dir() {
-            return false;
-        }
+    /// Re-reads the current directory, e.g. after a deck was created in it.
+    pub fn refresh(&mut self) -> std::io::Result<()> {
+        self.search = None;
+        self.tags.clear();
+        self.collection.refresh()
+    }
 
-        let trials = [FileType::Json(file.clone())];
+    pub fn current_path_is_file(&self) -> bool {
+        self.collection
+            .peek_index()
+            .is_some_and(|path| path.is_file())
+    }
 
-        for trial in trials {
-            if trial.load::<Deck<T>>().is_ok() {
-                return true;
-            }
+    /// The selected path, none when the directory is empty.
+    pub fn current_path(&self) -> Option<PathBuf> {
+        self.collection.peek_index().cloned()
+    }
+    pub fn is_deck<T: Expand + DeserializeOwned>(&self) -> bool {
+        match self.collection.peek_index() {
+            Some(file) if file.is_file() => FileType::from_path(file).load::<Deck<T>>().is_ok(),
+            _ => false,
         }
+    }
+}
 
-        false
+/// The tags of a deck and all of its cards, none if the file isn't a deck.
+fn deck_tags(path: &Path) -> Vec<String> {
+    if !path.is_file() {
+        return vec![];
+    }
+    let Ok(deck) = FileType::from_path(path).load::<Deck<Entry>>() else {
+        return vec![];
+    };
+    let mut tags = deck.tags().to_vec();
+    for entry in deck.cards() {
+        tags.extend(entry.tags().iter().cloned());
     }
+    tags
 }
@@ -0,0 +1,71 @@
+use crossterm::event::KeyCode;
+use ratatui::layout::Constraint;
+use ratatui::style::{Color, Stylize};
+use ratatui::text::Line;
+use ratatui::widgets::{Borders, Clear, Paragraph, Wrap};
+use ratatui::Frame;
+
+use super::inputs::{Input, KeyMap};
+use super::style::AppStyle;
+use super::{center, Intent, KadeuApp};
+
+const WIDTH: u16 = 60;
+
+/// A message in a box over the middle of the screen, dropped by any of its keys.
+pub struct Dialog {
+    title: String,
+    message: String,
+}
+
+impl Dialog {
+    pub fn new(title: &str, message: &str) -> Self {
+        Self {
+            title: title.to_string(),
+            message: message.to_string(),
+        }
+    }
+
+    /// Tells what went wrong instead of the app closing over it.
+    pub fn error(error: &impl std::fmt::Display) -> Self {
+        Self::new("Error", &error.to_string())
+    }
+}
+
+impl KadeuApp for Dialog {
+    fn handle_input(&mut self, input: Option<&Input>) -> std::io::Result<Intent> {
+        match input {
+            Some(Input::Select | Input::Escape) => Ok(Intent::Pop),
+            _ => Ok(Intent::None),
+        }
+    }
+
+    fn render(&mut self, frame: &mut Frame, style: &AppStyle) {
+        let block = style
+            .block()
+            .borders(Borders::ALL)
+            .border_style(Color::Red)
+            .title(self.title.clone().bold())
+            .title_bottom(Line::styled(" enter ok ", style.muted()).right_aligned());
+        let paragraph = Paragraph::new(self.message.as_str())
+            .wrap(Wrap { trim: false })
+            .block(block);
+        let width = WIDTH.min(frame.area().width);
+        // the count takes in the borders above and below, not the ones to the sides
+        let height = paragraph.line_count(width.saturating_sub(2)) as u16;
+        let area = center(
+            frame.area(),
+            Constraint::Length(width),
+            Constraint::Length(height),
+        );
+        frame.render_widget(Clear, area);
+        frame.render_widget(paragraph, area);
+    }
+
+    fn keymap(&self) -> KeyMap {
+        let mut map = KeyMap::new();
+        map.insert(KeyCode::Enter, Input::Select);
+        map.insert(KeyCode::Esc, Input::Escape);
+        map.insert(KeyCode::Char('q'), Input::Escape);
+        map
+    }
+}
@@ -0,0 +1,718 @@
+use std::path::{Path, PathBuf};
+
+use crossterm::event::KeyCode;
+use ratatui::layout::{Constraint, Layout, Position, Rect};
+use ratatui::text::{Line, Text};
+use ratatui::widgets::{ListState, Paragraph, Wrap};
+use ratatui::Frame;
+
+use super::inputs::{Input, KeyMap};
+use super::style::AppStyle;
+use super::{Intent, KadeuApp, Outcome};
+use crate::app::Deck;
+use crate::game::choice::Choice;
+use crate::game::cloze::Cloze;
+use crate::game::note::Note;
+use crate::game::tags;
+use crate::game::Kadeu;
+use crate::io::FileType;
+use crate::{Entry, Flashcard};
+
+/// A small multiline text buffer with a cursor.
+#[derive(Debug, Clone)]
+pub struct TextField {
+    lines: Vec<Vec<char>>,
+    row: usize,
+    col: usize,
+}
+
+impl From<&str> for TextField {
+    fn from(text: &str) -> Self {
+        let mut lines: Vec<Vec<char>> = text
+            .split('\n')
+            .map(|line| line.chars().collect())
+            .collect();
+        if lines.is_empty() {
+            lines.push(vec![]);
+        }
+        let row = lines.len() - 1;
+        let col = lines[row].len();
+        Self { lines, row, col }
+    }
+}
+
+impl TextField {
+    pub fn text(&self) -> String {
+        self.lines
+            .iter()
+            .map(|line| line.iter().collect::<String>())
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+
+    pub fn insert(&mut self, c: char) {
+        self.lines[self.row].insert(self.col, c);
+        self.col += 1;
+    }
+
+    pub fn newline(&mut self) {
+        let rest = self.lines[self.row].split_off(self.col);
+        self.row += 1;
+        self.col = 0;
+        self.lines.insert(self.row, rest);
+    }
+
+    pub fn backspace(&mut self) {
+        if self.col > 0 {
+            self.col -= 1;
+            self.lines[self.row].remove(self.col);
+        } else if self.row > 0 {
+            let line = self.lines.remove(self.row);
+            self.row -= 1;
+            self.col = self.lines[self.row].len();
+            self.lines[self.row].extend(line);
+        }
+    }
+
+    pub fn delete(&mut self) {
+        if self.col < self.lines[self.row].len() {
+            self.lines[self.row].remove(self.col);
+        } else if self.row + 1 < self.lines.len() {
+            let line = self.lines.remove(self.row + 1);
+            self.lines[self.row].extend(line);
+        }
+    }
+
+    pub fn left(&mut self) {
+        if self.col > 0 {
+            self.col -= 1;
+        } else if self.row > 0 {
+            self.row -= 1;
+            self.col = self.lines[self.row].len();
+        }
+    }
+
+    pub fn right(&mut self) {
+        if self.col < self.lines[self.row].len() {
+            self.col += 1;
+        } else if self.row + 1 < self.lines.len() {
+            self.row += 1;
+            self.col = 0;
+        }
+    }
+
+    pub fn up(&mut self) {
+        if self.row > 0 {
+            self.row -= 1;
+            self.col = self.col.min(self.lines[self.row].len());
+        }
+    }
+
+    pub fn down(&mut self) {
+        if self.row + 1 < self.lines.len() {
+            self.row += 1;
+            self.col = self.col.min(self.lines[self.row].len());
+        }
+    }
+
+    pub fn home(&mut self) {
+        self.col = 0;
+    }
+
+    pub fn end(&mut self) {
+        self.col = self.lines[self.row].len();
+    }
+
+    /// Applies a raw key press, returns false if the key isn't an editing key.
+    pub fn handle_key(&mut self, code: &KeyCode) -> bool {
+        match code {
+            KeyCode::Char(c) => self.insert(*c),
+            KeyCode::Enter => self.newline(),
+            KeyCode::Backspace => self.backspace(),
+            KeyCode::Delete => self.delete(),
+            KeyCode::Left => self.left(),
+            KeyCode::Right => self.right(),
+            KeyCode::Up => self.up(),
+            KeyCode::Down => self.down(),
+            KeyCode::Home => self.home(),
+            KeyCode::End => self.end(),
+            _ => return false,
+        }
+        true
+    }
+
+    /// Draws the buffer into the area and places the terminal cursor.
+    /// Long lines scroll sideways instead of wrapping so the cursor stays put.
+    pub fn render(&self, frame: &mut Frame, area: Rect) {
+        let height = area.height.max(1) as usize;
+        let width = area.width.max(1) as usize;
+        let y_offset = (self.row + 1).saturating_sub(height);
+        let x_offset = (self.col + 1).saturating_sub(width);
+        let text = Text::from(
+            self.lines
+                .iter()
+                .map(|line| Line::from(line.iter().collect::<String>()))
+                .collect::<Vec<Line>>(),
+        );
+        let paragraph = Paragraph::new(text).scroll((y_offset as u16, x_offset as u16));
+        frame.render_widget(paragraph, area);
+        frame.set_cursor_position(Position::new(
+            area.x + (self.col - x_offset) as u16,
+            area.y + (self.row - y_offset) as u16,
+        ));
+    }
+}
+
+/// Entries the editor knows how to lay out as a list of named text fields.
+pub trait Editable {
+    fn fields(&self) -> Vec<(String, String)>;
+    fn set_field(&mut self, field: usize, value: String);
+}
+
+impl Editable for Entry {
+    fn fields(&self) -> Vec<(String, String)> {
+        let named = |fields: Vec<(&str, String)>| {
+            fields
+                .into_iter()
+                .map(|(name, value)| (name.to_string(), value))
+                .collect()
+        };
+        let mut fields: Vec<(String, String)> = match self {
+            Self::Flashcard(card) => named(vec![
+                ("Front", card.front().to_string()),
+                ("Back", card.back().to_string()),
+                ("Hint", card.hint().unwrap_or_default().to_string()),
+                ("Notes", card.notes().unwrap_or_default().to_string()),
+            ]),
+            Self::Cloze(cloze) => named(vec![
+                ("Cloze", cloze.text().to_string()),
+                ("Hint", cloze.hint().unwrap_or_default().to_string()),
+                ("Notes", cloze.notes().unwrap_or_default().to_string()),
+            ]),
+            Self::Choice(choice) => named(vec![
+                ("Question", choice.question().to_string()),
+                ("Answer", choice.answer().to_string()),
+                // one per line, left empty to draw them from the rest of the deck
+                ("Distractors", choice.distractors().join("\n")),
+                ("Hint", choice.hint().unwrap_or_default().to_string()),
+                ("Notes", choice.notes().unwrap_or_default().to_string()),
+            ]),
+            Self::Note(note) => note.fields().to_vec(),
+        };
+        // tags always come last, separated by commas
+        fields.push(("Tags".to_string(), self.tags().join(", ")));
+        fields
+    }
+
+    fn set_field(&mut self, field: usize, value: String) {
+        if field + 1 == self.fields().len() {
+            self.set_tags(tags::parse(&value));
+            return;
+        }
+        match (self, field) {
+            (Self::Flashcard(card), 0) => card.set_front(value),
+            (Self::Flashcard(card), 1) => card.set_back(value),
+            (Self::Flashcard(card), 2) => card.set_hint(optional(value)),
+            (Self::Flashcard(card), 3) => card.set_notes(optional(value)),
+            (Self::Cloze(cloze), 0) => cloze.set_text(value),
+            (Self::Cloze(cloze), 1) => cloze.set_hint(optional(value)),
+            (Self::Cloze(cloze), 2) => cloze.set_notes(optional(value)),
+            (Self::Note(note), n) => note.set_field(n, value),
+            (Self::Choice(choice), 0) => choice.set_question(value),
+            (Self::Choice(choice), 1) => choice.set_answer(value),
+            (Self::Choice(choice), 2) => choice.set_distractors(
+                value
+                    .lines()
+                    .filter(|line| !line.trim().is_empty())
+                    .map(str::to_string)
+                    .collect(),
+            ),
+            (Self::Choice(choice), 3) => choice.set_hint(optional(value)),
+            (Self::Choice(choice), 4) => choice.set_notes(optional(value)),
+            _ => {}
+        }
+    }
+}
+
+/// Fields which may be left out of the deck file are dropped when emptied.
+fn optional(value: String) -> Option<String> {
+    Some(value).filter(|value| !value.trim().is_empty())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Field {
+    Title,
+    /// The tags of the whole deck.
+    Tags,
+    Card(usize),
+}
+
+enum Mode {
+    Browse,
+    Edit(Field, TextField),
+}
+
+/// Lets the user add, edit, delete and reorder the cards of a deck
+/// and writes the result back in the format the deck was read from.
+pub struct DeckEditor {
+    // None until a freshly created deck has been saved for the first time.
+    path: Option<PathBuf>,
+    directory: PathBuf,
+    deck: Deck<Entry>,
+    index: usize,
+    // the id of the card handed to focus, followed through edits of its own entry
+    focused: Option<String>,
+    mode: Mode,
+    dirty: bool,
+    confirm_exit: bool,
+    status: Option<String>,
+}
+
+impl DeckEditor {
+    /// Opens the deck at path, or starts an empty one there if the file doesn't exist yet.
+    pub fn open(path: &Path) -> std::io::Result<Self> {
+        let deck = if path.exists() {
+            FileType::from_path(path).load()?
+        } else {
+            let title = path
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().to_string())
+                .unwrap_or_default();
+            Deck::new(&title)
+        };
+        let directory = path.parent().map(Path::to_path_buf).unwrap_or_default();
+        Ok(Self::new(deck, Some(path.to_path_buf()), directory))
+    }
+
+    /// Starts a new deck which will be saved into directory,
+    /// its filename is taken from the title the user gives it.
+    pub fn create(directory: &Path) -> Self {
+        let mut editor = Self::new(Deck::new(""), None, directory.to_path_buf());
+        editor.edit(Field::Title);
+        editor
+    }
+
+    fn new(deck: Deck<Entry>, path: Option<PathBuf>, directory: PathBuf) -> Self {
+        Self {
+            path,
+            directory,
+            deck,
+            index: 0,
+            focused: None,
+            mode: Mode::Browse,
+            dirty: false,
+            confirm_exit: false,
+            status: None,
+        }
+    }
+
+    /// Selects the entry which produced the card with this front,
+    /// e.g. the cloze sentence behind a deletion.
+    pub fn focus(&mut self, front: &str) {
+        for (index, entry) in self.deck.cards().into_iter().enumerate() {
+            let cards = self.deck.expand_entry(entry);
+            if let Some(card) = cards.iter().find(|other| other.front() == front) {
+                self.index = index;
+                self.focused = Some(card.id());
+                return;
+            }
+        }
+    }
+
+    /// The card handed to focus as it reads after editing, None once its entry is gone.
+    pub fn focused(&self) -> Option<Flashcard> {
+        let id = self.focused.as_ref()?;
+        self.deck
+            .cards()
+            .into_iter()
+            .flat_map(|entry| self.deck.expand_entry(entry))
+            .find(|card| &card.id() == id)
+    }
+
+    /// Which of the selected entry's cards is the focused one.
+    fn focused_position(&self) -> Option<usize> {
+        let id = self.focused.as_ref()?;
+        let cards = self.deck.expand_entry(self.selected()?);
+        cards.iter().position(|card| &card.id() == id)
+    }
+
+    pub fn selected(&self) -> Option<&Entry> {
+        self.deck.cards().get(self.index).copied()
+    }
+
+    pub fn path(&self) -> Option<&PathBuf> {
+        self.path.as_ref()
+    }
+
+    fn len(&self) -> usize {
+        self.deck.cards().len()
+    }
+
+    fn edit(&mut self, field: Field) {
+        let text = match field {
+            Field::Title => self.deck.title().to_string(),
+            Field::Tags => self.deck.tags().join(", "),
+            Field::Card(n) => match self
+                .selected()
+                .and_then(|entry| entry.fields().get(n).cloned())
+            {
+                Some((_, text)) => text,
+                None => return,
+            },
+        };
+        self.mode = Mode::Edit(field, TextField::from(text.as_str()));
+    }
+
+    fn commit(&mut self) {
+        let Mode::Edit(field, input) = &self.mode else {
+            return;
+        };
+        let text = input.text();
+        match *field {
+            Field::Title => {
+                if self.deck.title() != text {
+                    self.deck.set_title(&text);
+                    self.dirty = true;
+                }
+            }
+            Field::Tags => {
+                let tags = tags::parse(&text);
+                if self.deck.tags() != tags {
+                    self.deck.set_tags(tags);
+                    self.dirty = true;
+                }
+            }
+            Field::Card(n) => {
+                // a card without an id of its own gets a new one when its front changes
+                let position = self.focused_position();
+                if let Some(entry) = self.deck.cards_mut().get_mut(self.index) {
+                    if entry.fields().get(n).map(|(_, old)| old) != Some(&text) {
+                        entry.set_field(n, text);
+                        self.dirty = true;
+                    }
+                }
+                if let Some(position) = position {
+                    let cards = self.selected().map(|entry| self.deck.expand_entry(entry));
+                    if let Some(card) = cards.as_ref().and_then(|cards| cards.get(position)) {
+                        self.focused = Some(card.id());
+                    }
+                }
+            }
+        }
+        self.mode = Mode::Browse;
+    }
+
+    fn add(&mut self, entry: Entry) {
+        let index = if self.len() == 0 { 0 } else { self.index + 1 };
+        self.deck.cards_mut().insert(index, entry);
+        self.index = index;
+        self.dirty = true;
+        self.edit(Field::Card(0));
+    }
+
+    fn delete(&mut self) {
+        if self.index < self.len() {
+            self.deck.cards_mut().remove(self.index);
+            self.index = self.index.min(self.len().saturating_sub(1));
+            self.dirty = true;
+        }
+    }
+
+    fn move_down(&mut self) {
+        if self.index + 1 < self.len() {
+            self.deck.cards_mut().swap(self.index, self.index + 1);
+            self.index += 1;
+            self.dirty = true;
+        }
+    }
+
+    fn move_up(&mut self) {
+        if self.index > 0 && self.index < self.len() {
+            self.deck.cards_mut().swap(self.index, self.index - 1);
+            self.index -= 1;
+            self.dirty = true;
+        }
+    }
+
+    fn save(&mut self) -> std::io::Result<()> {
+        if self.deck.title().trim().is_empty() {
+            self.status = Some("the deck needs a title before it can be saved".to_string());
+            return Ok(());
+        }
+        let path = match &self.path {
+            Some(path) => path.clone(),
+            None => available_path(&self.directory, &filename(self.deck.title())),
+        };
+        FileType::from_path(&path).save(&self.deck)?;
+        self.status = Some(format!("saved {}", path.to_string_lossy()));
+        self.path = Some(path);
+        self.dirty = false;
+        Ok(())
+    }
+
+    fn handle_browse(&mut self, input: &Input) -> std::io::Result<Intent> {
+        let Input::Key(code) = input else {
+            return Ok(Intent::None);
+        };
+        if !matches!(code, KeyCode::Char('q') | KeyCode::Esc) {
+            self.confirm_exit = false;
+        }
+        match code {
+            KeyCode::Char('j') | KeyCode::Down if self.index + 1 < self.len() => {
+                self.index += 1;
+            }
+            KeyCode::Char('k') | KeyCode::Up => {
+                self.index = self.index.saturating_sub(1);
+            }
+            KeyCode::Char('J') => self.move_down(),
+            KeyCode::Char('K') => self.move_up(),
+            KeyCode::Char('a') => self.add(Entry::Flashcard(Flashcard::new(
+                String::new(),
+                String::new(),
+            ))),
+            KeyCode::Char('c') => self.add(Entry::Cloze(Cloze::new(""))),
+            KeyCode::Char('o') => self.add(Entry::Choice(Choice::new("", ""))),
+            KeyCode::Char('n') if self.deck.fields().is_empty() => {
+                self.status = Some("the deck declares no fields to make notes from".to_string());
+            }
+            KeyCode::Char('n') => self.add(Entry::Note(Note::new(self.deck.fields()))),
+            KeyCode::Char('d') => self.delete(),
+            KeyCode::Char('e') | KeyCode::Enter => self.edit(Field::Card(0)),
+            KeyCode::Char('b') => self.edit(Field::Card(1)),
+            KeyCode::Char('t') => self.edit(Field::Title),
+            KeyCode::Char('g') => self.edit(Field::Tags),
+            KeyCode::Char('m') => {
+                self.deck.set_markdown(!self.deck.markdown());
+                self.dirty = true;
+            }
+            KeyCode::Char('s') => self.save()?,
+            KeyCode::Char('q') | KeyCode::Esc => {
+                if !self.dirty || self.confirm_exit {
+                    return Ok(Intent::Pop);
+                }
+                self.confirm_exit = true;
+                self.status =
+                    Some("unsaved changes, press s to save or q again to discard".to_string());
+            }
+            _ => {}
+        }
+        Ok(Intent::None)
+    }
+
+    fn handle_edit(&mut self, input: &Input) {
+        let Input::Key(code) = input else {
+            return;
+        };
+        let Mode::Edit(field, text) = &mut self.mode else {
+            return;
+        };
+        match (code, *field) {
+            (KeyCode::Esc, _) => self.commit(),
+            (KeyCode::Tab, Field::Card(n)) => {
+                let fields = self
+                    .selected()
+                    .map(|entry| entry.fields().len())
+                    .unwrap_or(1);
+                self.commit();
+                self.edit(Field::Card((n + 1) % fields));
+            }
+            // the title and deck tags are a single line
+            (KeyCode::Enter, Field::Title | Field::Tags) => self.commit(),
+            (code, _) => {
+                text.handle_key(code);
+            }
+        }
+    }
+
+    fn footer(&self) -> String {
+        if let Some(status) = &self.status {
+            return status.clone();
+        }
+        match self.mode {
+            Mode::Browse => "a/c/o/n add card/cloze/choice/note  e/b edit  d delete  J/K move  t title  g tags  m markdown  s save  q back"
+                .to_string(),
+            Mode::Edit(_, _) => "Tab next field  Esc done".to_string(),
+        }
+    }
+}
+
+impl KadeuApp for DeckEditor {
+    fn handle_input(&mut self, input: Option<&Input>) -> std::io::Result<Intent> {
+        let Some(input) = input else {
+            return Ok(Intent::None);
+        };
+        self.status = None;
+        if let Mode::Browse = self.mode {
+            self.handle_browse(input)
+        } else {
+            self.handle_edit(input);
+            Ok(Intent::None)
+        }
+    }
+
+    fn render(&mut self, frame: &mut Frame, style: &AppStyle) {
+        let summaries: Vec<String> = self
+            .deck
+            .cards()
+            .iter()
+            .map(|entry| {
+                let (_, text) = entry.fields().into_iter().next().unwrap_or_default();
+                text.lines().next().unwrap_or_default().to_string()
+            })
+            .collect();
+        let mut state = ListState::default();
+        state.select(Some(self.index));
+
+        let title = if self.dirty {
+            format!("{} *", self.deck.title())
+        } else {
+            self.deck.title().to_string()
+        };
+        let list = style
+            .list(summaries)
+            .highlight_symbol("> ")
+            .block(style.block().title(title));
+        let fields = self.selected().map(Editable::fields).unwrap_or_default();
+        let footer = self.footer();
+
+        let editing_deck = matches!(self.mode, Mode::Edit(Field::Title | Field::Tags, _));
+
+        let [title_area, main, footer_area] = Layout::vertical([
+            Constraint::Length(if editing_deck { 3 } else { 0 }),
+            Constraint::Min(3),
+            Constraint::Length(1),
+        ])
+        .areas(frame.area());
+        let [list_area, card_area] =
+            Layout::horizontal([Constraint::Percentage(35), Constraint::Percentage(65)])
+                .areas(main);
+        let areas =
+            Layout::vertical(vec![Constraint::Fill(1); fields.len().max(1)]).split(card_area);
+
+        frame.render_stateful_widget(list, list_area, &mut state);
+
+        for (n, ((label, text), area)) in fields.into_iter().zip(areas.iter()).enumerate() {
+            let block = style.block().title(label);
+            let inner = block.inner(*area);
+            frame.render_widget(block, *area);
+            match &self.mode {
+                Mode::Edit(Field::Card(editing), input) if *editing == n => {
+                    input.render(frame, inner)
+                }
+                _ => {
+                    frame.render_widget(Paragraph::new(text).wrap(Wrap { trim: false }), inner);
+                }
+            }
+        }
+
+        if let Mode::Edit(field @ (Field::Title | Field::Tags), input) = &self.mode {
+            let label = if *field == Field::Title {
+                "Title"
+            } else {
+                "Deck tags"
+            };
+            let block = style.block().title(label);
+            let inner = block.inner(title_area);
+            frame.render_widget(block, title_area);
+            input.render(frame, inner);
+        }
+
+        frame.render_widget(Paragraph::new(footer), footer_area);
+    }
+
+    fn keymap(&self) -> KeyMap {
+        // every key is handled by the editor itself
+        KeyMap::new()
+    }
+
+    fn disable_universal_keymap(&self) -> bool {
+        true
+    }
+
+    fn outcome(&mut self) -> Option<Outcome> {
+        self.focused().map(Outcome::Edited)
+    }
+}
+
+/// Turns a deck title into a json filename.
+fn filename(title: &str) -> String {
+    let stem: String = title
+        .trim()
+        .to_lowercase()
+        .chars()
+        .map(|c| if c.is_alphanumeric() { c } else { '_' })
+        .collect();
+    format!("{}.json", stem)
+}
+
+/// Finds a path in directory that won't clobber an existing deck.
+fn available_path(directory: &Path, filename: &str) -> PathBuf {
+    let mut path = directory.join(filename);
+    let mut n = 1;
+    while path.exists() {
+        let stem = Path::new(filename)
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())
+            .unwrap_or_default();
+        path = directory.join(format!("{}_{}.json", stem, n));
+        n += 1;
+    }
+    path
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use crossterm::event::KeyCode;
+
+    use super::DeckEditor;
+    use crate::app::Deck;
+    use crate::game::engine::ui::FlashcardApp;
+    use crate::game::schedule::Due;
+    use crate::game::{Direction, Kadeu};
+    use crate::ui::inputs::Input;
+    use crate::ui::{Intent, KadeuApp};
+    use crate::{Entry, Flashcard};
+
+    #[test]
+    fn edits_go_back_to_the_focused_card() {
+        let card = |front: &str, back: &str| Flashcard::new(front.to_string(), back.to_string());
+        let items = vec![Due {
+            deck: "german".to_string(),
+            card: card("hund", "dog"),
+            direction: Direction::Forward,
+        }];
+        let mut app = FlashcardApp::new("german", false, items);
+        app.handle_input(Some(&Input::Continue)).unwrap();
+
+        let deck = Deck::<Entry>::new("german").with_cards(vec![
+            Entry::Flashcard(card("hund", "dog")),
+            Entry::Flashcard(card("katze", "cat")),
+        ]);
+        let mut editor = DeckEditor::new(deck, None, PathBuf::new());
+        editor.focus("hund");
+        let keys = ['b', '!'].map(KeyCode::Char);
+        for code in keys.into_iter().chain([KeyCode::Esc, KeyCode::Char('j')]) {
+            editor.handle_input(Some(&Input::Key(code))).unwrap();
+        }
+        // moving on to another card leaves the one being studied as the one replaced
+        let mut intent = Intent::None;
+        for _ in 0..2 {
+            intent = editor
+                .handle_input(Some(&Input::Key(KeyCode::Char('q'))))
+                .unwrap();
+        }
+        assert!(matches!(intent, Intent::Pop));
+        app.resume(editor.outcome()).unwrap();
+        let current = app.current().unwrap();
+        assert_eq!(
+            (current.front(), current.back()),
+            (&"hund".to_string(), &"dog!".to_string())
+        );
+
+        editor.delete();
+        editor.delete();
+        assert!(editor.outcome().is_none());
+    }
+}
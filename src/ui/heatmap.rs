@@ -0,0 +1,95 @@
+use chrono::{Datelike, Duration, NaiveDate};
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::text::Line;
+use ratatui::widgets::Widget;
+
+use super::style::AppStyle;
+use crate::game::stats::Day;
+
+// room for the weekday names on the left
+const LABEL_WIDTH: u16 = 4;
+const SYMBOLS: [&str; 5] = ["·", "░", "▒", "▓", "█"];
+
+/// Reviews a day as a calendar, one column per week with Monday at the top
+/// and the latest week on the right. Needs 8 lines of height.
+pub struct Heatmap<'a> {
+    days: &'a [Day],
+    style: &'a AppStyle,
+    goal: Option<usize>,
+}
+
+impl<'a> Heatmap<'a> {
+    /// The days are given oldest first.
+    pub fn new(days: &'a [Day], style: &'a AppStyle) -> Self {
+        Self {
+            days,
+            style,
+            goal: None,
+        }
+    }
+
+    /// Days meeting the goal are drawn at their brightest, otherwise it is the busiest day.
+    pub fn goal(mut self, goal: Option<usize>) -> Self {
+        self.goal = goal;
+        self
+    }
+
+    fn level(&self, count: usize, busiest: usize) -> u8 {
+        let full = self.goal.unwrap_or(busiest).max(1);
+        if count == 0 {
+            0
+        } else {
+            (count * 4).div_ceil(full).clamp(1, 4) as u8
+        }
+    }
+}
+
+impl Widget for Heatmap<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let (Some(first), Some(last)) = (self.days.first(), self.days.last()) else {
+            return;
+        };
+        if area.width <= LABEL_WIDTH || area.height < 8 {
+            return;
+        }
+        let busiest = self.days.iter().map(|day| day.count).max().unwrap_or(0);
+        let count = |date: NaiveDate| {
+            let index = (date - first.date).num_days();
+            usize::try_from(index)
+                .ok()
+                .and_then(|index| self.days.get(index))
+                .map(|day| day.count)
+        };
+
+        let weeks = ((area.width - LABEL_WIDTH) / 2) as i64;
+        let monday = last.date - Duration::days(last.date.weekday().num_days_from_monday() as i64);
+        let start = monday - Duration::weeks(weeks - 1);
+        for (row, name) in [(0, "Mon"), (2, "Wed"), (4, "Fri")] {
+            Line::styled(name, self.style.muted())
+                .render(Rect::new(area.x, area.y + 1 + row, LABEL_WIDTH, 1), buf);
+        }
+        for week in 0..weeks {
+            let x = area.x + LABEL_WIDTH + week as u16 * 2;
+            let week_start = start + Duration::weeks(week);
+            // months are named above the week they start in
+            if week_start.day() <= 7 && x + 3 <= area.right() {
+                let month = week_start.format("%b").to_string();
+                buf.set_string(x, area.y, month, self.style.muted());
+            }
+            for weekday in 0..7 {
+                let date = week_start + Duration::days(weekday);
+                let Some(count) = count(date) else {
+                    continue;
+                };
+                let level = self.level(count, busiest);
+                buf.set_string(
+                    x,
+                    area.y + 1 + weekday as u16,
+                    SYMBOLS[level as usize],
+                    self.style.heat(level),
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,341 @@
+use ratatui::{
+    style::Style,
+    text::{Line, Span, Text},
+};
+
+use super::style::AppStyle;
+
+// Tabs are expanded so indentation comes out the same in every terminal.
+const TAB: &str = "    ";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Language {
+    Rust,
+    Python,
+    Sql,
+    Shell,
+}
+
+impl Language {
+    fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "rust" | "rs" => Some(Self::Rust),
+            "python" | "py" | "python3" => Some(Self::Python),
+            "sql" | "psql" | "mysql" | "sqlite" => Some(Self::Sql),
+            "sh" | "shell" | "bash" | "zsh" | "console" => Some(Self::Shell),
+            _ => None,
+        }
+    }
+
+    fn keywords(&self) -> &'static [&'static str] {
+        match self {
+            Self::Rust => &[
+                "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else",
+                "enum", "extern", "false", "fn", "for", "if", "impl", "in", "let", "loop", "match",
+                "mod", "move", "mut", "pub", "ref", "return", "self", "Self", "static", "struct",
+                "super", "trait", "true", "type", "unsafe", "use", "where", "while",
+            ],
+            Self::Python => &[
+                "False", "None", "True", "and", "as", "assert", "async", "await", "break", "class",
+                "continue", "def", "del", "elif", "else", "except", "finally", "for", "from",
+                "global", "if", "import", "in", "is", "lambda", "nonlocal", "not", "or", "pass",
+                "raise", "return", "try", "while", "with", "yield",
+            ],
+            Self::Sql => &[
+                "add", "all", "alter", "and", "as", "asc", "between", "by", "case", "create",
+                "delete", "desc", "distinct", "drop", "else", "end", "exists", "false", "from",
+                "full", "group", "having", "in", "index", "inner", "insert", "into", "is", "join",
+                "key", "left", "like", "limit", "not", "null", "offset", "on", "or", "order",
+                "outer", "primary", "right", "select", "set", "table", "then", "true", "union",
+                "update", "values", "view", "when", "where", "with",
+            ],
+            Self::Shell => &[
+                "case", "do", "done", "elif", "else", "esac", "export", "fi", "for", "function",
+                "if", "in", "local", "return", "then", "until", "while",
+            ],
+        }
+    }
+
+    fn is_keyword(&self, word: &str) -> bool {
+        match self {
+            // sql is written in whichever case the author likes
+            Self::Sql => self.keywords().contains(&word.to_lowercase().as_str()),
+            _ => self.keywords().contains(&word),
+        }
+    }
+
+    fn line_comment(&self) -> &'static str {
+        match self {
+            Self::Rust => "//",
+            Self::Python | Self::Shell => "#",
+            Self::Sql => "--",
+        }
+    }
+
+    fn block_comment(&self) -> Option<(&'static str, &'static str)> {
+        match self {
+            Self::Rust | Self::Sql => Some(("/*", "*/")),
+            Self::Python | Self::Shell => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Token {
+    Plain,
+    Keyword,
+    Str,
+    Comment,
+    Number,
+    Function,
+    Type,
+}
+
+impl Token {
+    fn style(&self, style: &AppStyle) -> Style {
+        match self {
+            Self::Plain => style.code(),
+            Self::Keyword => style.keyword(),
+            Self::Str => style.string(),
+            Self::Comment => style.muted(),
+            Self::Number => style.number(),
+            Self::Function => style.function(),
+            Self::Type => style.type_name(),
+        }
+    }
+}
+
+/// Splits code into lines of styled spans.
+/// Languages that aren't recognised come out in the plain code style.
+pub fn highlight(code: &str, language: &str, style: &AppStyle) -> Vec<Line<'static>> {
+    let code = code.replace('\t', TAB);
+    let Some(language) = Language::from_name(language) else {
+        let plain = style.code();
+        return code
+            .split('\n')
+            .map(|line| Line::from(Span::styled(line.to_string(), plain)))
+            .collect();
+    };
+
+    let mut lines = vec![];
+    let mut spans = vec![];
+    for (token, content) in tokenize(&code, language) {
+        // tokens such as block comments and strings can run over several lines
+        let mut parts = content.split('\n');
+        if let Some(first) = parts.next() {
+            if !first.is_empty() {
+                spans.push(Span::styled(first.to_string(), token.style(style)));
+            }
+        }
+        for part in parts {
+            lines.push(Line::from(std::mem::take(&mut spans)));
+            if !part.is_empty() {
+                spans.push(Span::styled(part.to_string(), token.style(style)));
+            }
+        }
+    }
+    lines.push(Line::from(spans));
+    lines
+}
+
+/// Highlights the ``` fenced blocks in otherwise plain text,
+/// so cards which aren't rendered as markdown still get coloured code.
+pub fn fenced(source: &str, style: &AppStyle) -> Text<'static> {
+    let mut lines = vec![];
+    let mut block: Option<(String, Vec<&str>)> = None;
+
+    for line in source.split('\n') {
+        let fence = line.trim_start().strip_prefix("```");
+        match (block.take(), fence) {
+            (None, Some(language)) => block = Some((language.to_string(), vec![])),
+            (None, None) => lines.push(Line::from(line.to_string())),
+            (Some((language, code)), Some(_)) => {
+                lines.extend(code_block(&code.join("\n"), &language, style));
+            }
+            (Some((language, mut code)), None) => {
+                code.push(line);
+                block = Some((language, code));
+            }
+        }
+    }
+
+    // an unclosed fence runs to the end of the face
+    if let Some((language, code)) = block {
+        lines.extend(code_block(&code.join("\n"), &language, style));
+    }
+    Text::from(lines)
+}
+
+/// Highlights a block of code and pads every line to the same width,
+/// so the indentation survives the block being centered.
+pub fn code_block(code: &str, language: &str, style: &AppStyle) -> Vec<Line<'static>> {
+    let mut lines = highlight(code, language, style);
+    let width = lines.iter().map(Line::width).max().unwrap_or(0);
+    for line in lines.iter_mut() {
+        let padding = width - line.width();
+        if padding > 0 {
+            line.push_span(Span::raw(" ".repeat(padding)));
+        }
+    }
+    lines
+}
+
+fn tokenize(code: &str, language: Language) -> Vec<(Token, String)> {
+    let chars: Vec<char> = code.chars().collect();
+    let mut tokens: Vec<(Token, String)> = vec![];
+    let mut i = 0;
+
+    let starts_with = |i: usize, pattern: &str| {
+        pattern
+            .chars()
+            .enumerate()
+            .all(|(n, c)| chars.get(i + n) == Some(&c))
+    };
+    let mut push = |token: Token, content: String| match tokens.last_mut() {
+        Some((last, text)) if *last == token => text.push_str(&content),
+        _ => tokens.push((token, content)),
+    };
+
+    while i < chars.len() {
+        let c = chars[i];
+        let start = i;
+
+        if starts_with(i, language.line_comment()) {
+            while i < chars.len() && chars[i] != '\n' {
+                i += 1;
+            }
+            push(Token::Comment, chars[start..i].iter().collect());
+            continue;
+        }
+
+        if let Some((open, close)) = language.block_comment() {
+            if starts_with(i, open) {
+                i += open.len();
+                while i < chars.len() && !starts_with(i, close) {
+                    i += 1;
+                }
+                i = (i + close.len()).min(chars.len());
+                push(Token::Comment, chars[start..i].iter().collect());
+                continue;
+            }
+        }
+
+        if c == '"' || c == '\'' || (c == '`' && language == Language::Shell) {
+            // rust lifetimes look like the start of a char literal
+            if c == '\'' && language == Language::Rust && !is_char_literal(&chars[i..]) {
+                i += 1;
+                while i < chars.len() && is_ident(chars[i]) {
+                    i += 1;
+                }
+                push(Token::Type, chars[start..i].iter().collect());
+                continue;
+            }
+
+            let triple = language == Language::Python && starts_with(i, &c.to_string().repeat(3));
+            let quote = if triple {
+                c.to_string().repeat(3)
+            } else {
+                c.to_string()
+            };
+            i += quote.chars().count();
+            while i < chars.len() && !starts_with(i, &quote) {
+                if chars[i] == '\\' {
+                    i += 1;
+                }
+                i += 1;
+            }
+            i = (i + quote.chars().count()).min(chars.len());
+            push(Token::Str, chars[start..i].iter().collect());
+            continue;
+        }
+
+        if c == '$' && language == Language::Shell {
+            i += 1;
+            if chars.get(i) == Some(&'{') {
+                while i < chars.len() && chars[i] != '}' {
+                    i += 1;
+                }
+                i = (i + 1).min(chars.len());
+            } else {
+                while i < chars.len() && is_ident(chars[i]) {
+                    i += 1;
+                }
+            }
+            push(Token::Type, chars[start..i].iter().collect());
+            continue;
+        }
+
+        if c.is_ascii_digit() {
+            while i < chars.len()
+                && (chars[i].is_alphanumeric() || chars[i] == '.' || chars[i] == '_')
+            {
+                i += 1;
+            }
+            push(Token::Number, chars[start..i].iter().collect());
+            continue;
+        }
+
+        if is_ident(c) {
+            while i < chars.len() && is_ident(chars[i]) {
+                i += 1;
+            }
+            let word: String = chars[start..i].iter().collect();
+            let token = if language.is_keyword(&word) {
+                Token::Keyword
+            } else if chars.get(i) == Some(&'(') || chars.get(i) == Some(&'!') {
+                Token::Function
+            } else if language == Language::Rust && c.is_uppercase() {
+                Token::Type
+            } else {
+                Token::Plain
+            };
+            push(token, word);
+            continue;
+        }
+
+        i += 1;
+        push(Token::Plain, c.to_string());
+    }
+    tokens
+}
+
+fn is_ident(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+/// True for 'a' and '\n', false for lifetimes such as 'a and 'static.
+fn is_char_literal(chars: &[char]) -> bool {
+    matches!(chars, ['\'', '\\', ..] | ['\'', _, '\'', ..])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::highlight;
+    use crate::ui::style::AppStyle;
+
+    #[test]
+    fn keeps_indentation_and_lines() {
+        let code = "fn main() {\n\tlet x = \"a\\\"b\"; // done\n}";
+        let lines: Vec<String> = highlight(code, "rust", &AppStyle::default())
+            .iter()
+            .map(|line| line.to_string())
+            .collect();
+        assert_eq!(
+            lines,
+            vec!["fn main() {", "    let x = \"a\\\"b\"; // done", "}"]
+        );
+    }
+
+    #[test]
+    fn keywords_are_styled() {
+        let style = AppStyle::default();
+        let lines = highlight("SELECT name FROM users", "sql", &style);
+        let keywords: Vec<String> = lines[0]
+            .spans
+            .iter()
+            .filter(|span| span.style == style.keyword())
+            .map(|span| span.content.to_string())
+            .collect();
+        assert_eq!(keywords, vec!["SELECT", "FROM"]);
+    }
+}
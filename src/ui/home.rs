@@ -0,0 +1,137 @@
+use std::path::PathBuf;
+
+use crossterm::event::KeyCode;
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::Stylize;
+use ratatui::text::Line;
+use ratatui::widgets::{Paragraph, Row, Table, TableState};
+use ratatui::Frame;
+
+use super::inputs::{Input, KeyMap};
+use super::style::AppStyle;
+use super::{Intent, KadeuApp, Outcome, Screen};
+use crate::game::schedule::Counts;
+
+/// One line of the home screen.
+#[derive(Debug, Clone)]
+pub struct DeckSummary {
+    pub path: PathBuf,
+    /// Where the deck sits in the import directory.
+    pub name: String,
+    pub counts: Counts,
+}
+
+/// Lists every deck with what is waiting in it today.
+pub struct Home {
+    decks: Vec<DeckSummary>,
+    index: usize,
+}
+
+impl Home {
+    pub fn new(decks: Vec<DeckSummary>) -> Self {
+        Self { decks, index: 0 }
+    }
+
+    /// The deck under the cursor, if there are any decks.
+    pub fn selected(&self) -> Option<&DeckSummary> {
+        self.decks.get(self.index)
+    }
+}
+
+impl KadeuApp for Home {
+    fn handle_input(&mut self, input: Option<&Input>) -> std::io::Result<Intent> {
+        let Some(input) = input else {
+            return Ok(Intent::None);
+        };
+        match input {
+            Input::Up => self.index = self.index.saturating_sub(1),
+            Input::Down if self.index + 1 < self.decks.len() => self.index += 1,
+            Input::Select => {
+                if let Some(deck) = self.selected() {
+                    return Ok(Intent::Push(Screen::Study(vec![deck.path.clone()])));
+                }
+            }
+            Input::Key(KeyCode::Char('b')) => return Ok(Intent::Push(Screen::Browse)),
+            _ => {}
+        }
+        Ok(Intent::None)
+    }
+
+    /// The counts are out of date after a session, so the screen is made again.
+    fn resume(&mut self, _outcome: Option<Outcome>) -> std::io::Result<Intent> {
+        Ok(Intent::Replace(Screen::Home))
+    }
+
+    fn render(&mut self, frame: &mut Frame, style: &AppStyle) {
+        let total = self
+            .decks
+            .iter()
+            .fold(Counts::default(), |total, deck| Counts {
+                new: total.new + deck.counts.new,
+                learning: total.learning + deck.counts.learning,
+                due: total.due + deck.counts.due,
+            });
+        let number = |n: usize| {
+            if n == 0 {
+                "·".to_string()
+            } else {
+                n.to_string()
+            }
+        };
+        let rows: Vec<Row> = self
+            .decks
+            .iter()
+            .map(|deck| {
+                Row::new(vec![
+                    deck.name.clone(),
+                    number(deck.counts.new),
+                    number(deck.counts.learning),
+                    number(deck.counts.due),
+                ])
+            })
+            .collect();
+        let widths = [
+            Constraint::Fill(1),
+            Constraint::Length(8),
+            Constraint::Length(8),
+            Constraint::Length(8),
+        ];
+        let table = Table::new(rows, widths)
+            .header(Row::new(vec!["Deck", "New", "Learning", "Due"]).bold())
+            .footer(
+                Row::new(vec![
+                    "Total".to_string(),
+                    total.new.to_string(),
+                    total.learning.to_string(),
+                    total.due.to_string(),
+                ])
+                .style(style.muted()),
+            )
+            .highlight_symbol("> ")
+            .block(style.block().title("Today"));
+        let footer = Line::styled("enter study · b browse · q quit", style.muted());
+        let mut state = TableState::default();
+        state.select(Some(self.index));
+
+        let [main, footer_area] =
+            Layout::vertical([Constraint::Min(3), Constraint::Length(1)]).areas(frame.area());
+        if self.decks.is_empty() {
+            let text = "No decks yet, press b to browse or import one";
+            frame.render_widget(Paragraph::new(text).block(style.block()), main);
+        } else {
+            frame.render_stateful_widget(table, main, &mut state);
+        }
+        frame.render_widget(Paragraph::new(footer), footer_area);
+    }
+
+    fn keymap(&self) -> KeyMap {
+        let mut map = KeyMap::new();
+        map.insert(KeyCode::Char('q'), Input::Quit);
+        map.insert(KeyCode::Char('j'), Input::Down);
+        map.insert(KeyCode::Down, Input::Down);
+        map.insert(KeyCode::Char('k'), Input::Up);
+        map.insert(KeyCode::Up, Input::Up);
+        map.insert(KeyCode::Enter, Input::Select);
+        map
+    }
+}
@@ -13,6 +13,9 @@ pub enum Input {
     Select,
     Escape,
     Quit,
+    /// A key press the keymap doesn't know about.
+    /// Apps that take free text (such as the editor) read their characters from here.
+    Key(KeyCode),
 }
 
 pub type KeyMap = HashMap<KeyCode, Input>;
@@ -35,14 +38,18 @@ impl From<KeyMap> for Events {
 type Timeout = std::time::Duration;
 
 impl Events {
-    pub fn poll(&self, timeout: u64) -> std::io::Result<Option<&Input>> {
+    pub fn poll(&self, timeout: u64) -> std::io::Result<Option<Input>> {
         // listens for an event and distrbutes it to its listenrs.
 
         //shoot looks ugly af
         let event = if event::poll(Timeout::from_millis(timeout))? {
             if let Event::Key(event) = event::read()? {
                 match event.kind {
-                    event::KeyEventKind::Press => get_input(&self.keymap, &event.code),
+                    event::KeyEventKind::Press => Some(
+                        get_input(&self.keymap, &event.code)
+                            .cloned()
+                            .unwrap_or(Input::Key(event.code)),
+                    ),
                     _ => None,
                 }
             } else {
@@ -0,0 +1,348 @@
+use pulldown_cmark::{CodeBlockKind, Event, HeadingLevel, Options, Parser, Tag, TagEnd};
+use ratatui::{
+    style::{Style, Stylize},
+    text::{Line, Span, Text},
+};
+
+use super::highlight;
+use super::style::AppStyle;
+
+/// Renders the markdown source of a card face into styled text.
+/// Top level blocks keep the alignment of whatever they are drawn into, tables and code
+/// are padded into even blocks so they survive being centered. Lists and quotes are left aligned.
+pub fn render(source: &str, style: &AppStyle) -> Text<'static> {
+    let options = Options::ENABLE_TABLES | Options::ENABLE_STRIKETHROUGH;
+    let mut writer = Writer::new(style);
+    for event in Parser::new_ext(source, options) {
+        writer.event(event);
+    }
+    writer.finish()
+}
+
+enum Container {
+    Quote,
+    // width of the marker so continuation lines line up under the item text
+    Item(usize),
+}
+
+#[derive(Default)]
+struct Table {
+    rows: Vec<Vec<Vec<Span<'static>>>>,
+}
+
+struct Writer<'s> {
+    style: &'s AppStyle,
+    lines: Vec<Line<'static>>,
+    spans: Vec<Span<'static>>,
+    inline: Vec<Style>,
+    containers: Vec<Container>,
+    // next number of each (nested) list, None for bullet lists
+    lists: Vec<Option<u64>>,
+    marker: Option<String>,
+    table: Option<Table>,
+    // language and source of the code block being read
+    code: Option<(String, String)>,
+}
+
+impl<'s> Writer<'s> {
+    fn new(style: &'s AppStyle) -> Self {
+        Self {
+            style,
+            lines: vec![],
+            spans: vec![],
+            inline: vec![],
+            containers: vec![],
+            lists: vec![],
+            marker: None,
+            table: None,
+            code: None,
+        }
+    }
+
+    fn finish(mut self) -> Text<'static> {
+        self.flush();
+        Text::from(self.lines)
+    }
+
+    fn current_style(&self) -> Style {
+        self.inline
+            .iter()
+            .fold(Style::new(), |style, inner| style.patch(*inner))
+    }
+
+    fn push(&mut self, content: String) {
+        let style = self.current_style();
+        self.push_span(Span::styled(content, style));
+    }
+
+    fn push_span(&mut self, span: Span<'static>) {
+        match self.table.as_mut().and_then(|table| table.rows.last_mut()) {
+            Some(row) => match row.last_mut() {
+                Some(cell) => cell.push(span),
+                None => row.push(vec![span]),
+            },
+            None => self.spans.push(span),
+        }
+    }
+
+    /// Leading spans for a new line given the containers it is nested in.
+    fn prefix(&mut self) -> Vec<Span<'static>> {
+        let mut prefix = vec![];
+        let innermost = self
+            .containers
+            .iter()
+            .rposition(|container| matches!(container, Container::Item(_)));
+        for (i, container) in self.containers.iter().enumerate() {
+            match container {
+                Container::Quote => prefix.push(Span::styled("│ ", self.style.muted())),
+                Container::Item(width) => match self.marker.take() {
+                    Some(marker) if Some(i) == innermost => prefix.push(Span::raw(marker)),
+                    marker => {
+                        self.marker = marker;
+                        prefix.push(Span::raw(" ".repeat(*width)));
+                    }
+                },
+            }
+        }
+        prefix
+    }
+
+    fn line(&mut self, spans: Vec<Span<'static>>) {
+        let mut content = self.prefix();
+        content.extend(spans);
+        let line = Line::from(content);
+        if self.containers.is_empty() {
+            self.lines.push(line);
+        } else {
+            self.lines.push(line.left_aligned());
+        }
+    }
+
+    /// Ends the line being written, if there is one.
+    fn flush(&mut self) {
+        if !self.spans.is_empty() || self.marker.is_some() {
+            let spans = std::mem::take(&mut self.spans);
+            self.line(spans);
+        }
+    }
+
+    /// Blocks at the top level are separated by an empty line.
+    fn separate(&mut self) {
+        self.flush();
+        if self.containers.is_empty() && !self.lines.is_empty() {
+            self.lines.push(Line::default());
+        }
+    }
+
+    fn event(&mut self, event: Event) {
+        match event {
+            Event::Start(tag) => self.start(tag),
+            Event::End(tag) => self.end(tag),
+            Event::Text(text) => match self.code.as_mut() {
+                Some((_, code)) => code.push_str(&text),
+                None => self.push(text.to_string()),
+            },
+            Event::Code(code) => {
+                let style = self.current_style().patch(self.style.code());
+                self.push_span(Span::styled(code.to_string(), style));
+            }
+            // card authors lay their faces out line by line, keep their breaks.
+            Event::SoftBreak | Event::HardBreak => {
+                let spans = std::mem::take(&mut self.spans);
+                self.line(spans);
+            }
+            Event::Rule => {
+                self.separate();
+                self.lines
+                    .push(Line::styled("───", self.style.muted()).centered());
+            }
+            Event::Html(html) | Event::InlineHtml(html) => self.push(html.to_string()),
+            _ => {}
+        }
+    }
+
+    fn start(&mut self, tag: Tag) {
+        match tag {
+            Tag::Paragraph => self.separate(),
+            Tag::Heading { level, .. } => {
+                self.separate();
+                let style = match level {
+                    HeadingLevel::H1 => Style::new().bold().underlined(),
+                    _ => Style::new().bold(),
+                };
+                self.inline.push(style);
+            }
+            Tag::BlockQuote(_) => {
+                self.separate();
+                self.containers.push(Container::Quote);
+                self.inline.push(Style::new().italic());
+            }
+            Tag::CodeBlock(kind) => {
+                self.separate();
+                let language = match kind {
+                    CodeBlockKind::Fenced(info) => info
+                        .split_whitespace()
+                        .next()
+                        .unwrap_or_default()
+                        .to_string(),
+                    CodeBlockKind::Indented => String::new(),
+                };
+                self.code = Some((language, String::new()));
+            }
+            Tag::List(start) => {
+                if self.lists.is_empty() {
+                    self.separate();
+                } else {
+                    self.flush();
+                }
+                self.lists.push(start);
+            }
+            Tag::Item => {
+                self.flush();
+                let marker = match self.lists.last_mut() {
+                    Some(Some(n)) => {
+                        *n += 1;
+                        format!("{}. ", *n - 1)
+                    }
+                    _ => "• ".to_string(),
+                };
+                self.containers
+                    .push(Container::Item(marker.chars().count()));
+                self.marker = Some(marker);
+            }
+            Tag::Table(_) => {
+                self.separate();
+                self.table = Some(Table::default());
+            }
+            Tag::TableHead => {
+                if let Some(table) = self.table.as_mut() {
+                    table.rows.push(vec![]);
+                }
+                self.inline.push(Style::new().bold());
+            }
+            Tag::TableRow => {
+                if let Some(table) = self.table.as_mut() {
+                    table.rows.push(vec![]);
+                }
+            }
+            Tag::TableCell => {
+                if let Some(row) = self.table.as_mut().and_then(|table| table.rows.last_mut()) {
+                    row.push(vec![]);
+                }
+            }
+            Tag::Emphasis => self.inline.push(Style::new().italic()),
+            Tag::Strong => self.inline.push(Style::new().bold()),
+            Tag::Strikethrough => self.inline.push(Style::new().crossed_out()),
+            Tag::Link { .. } => self.inline.push(Style::new().underlined()),
+            _ => {}
+        }
+    }
+
+    fn end(&mut self, tag: TagEnd) {
+        match tag {
+            TagEnd::Paragraph => self.flush(),
+            TagEnd::Heading(_) => {
+                self.flush();
+                self.inline.pop();
+            }
+            TagEnd::BlockQuote(_) => {
+                self.flush();
+                self.containers.pop();
+                self.inline.pop();
+            }
+            TagEnd::CodeBlock => {
+                let (language, code) = self.code.take().unwrap_or_default();
+                let code = code.trim_end_matches('\n');
+                for line in highlight::code_block(code, &language, self.style) {
+                    self.line(line.spans);
+                }
+            }
+            TagEnd::List(_) => {
+                self.flush();
+                self.lists.pop();
+            }
+            TagEnd::Item => {
+                self.flush();
+                self.containers.pop();
+            }
+            TagEnd::Table => {
+                if let Some(table) = self.table.take() {
+                    self.table(table);
+                }
+            }
+            TagEnd::TableHead => {
+                self.inline.pop();
+            }
+            TagEnd::Emphasis | TagEnd::Strong | TagEnd::Strikethrough | TagEnd::Link => {
+                self.inline.pop();
+            }
+            _ => {}
+        }
+    }
+
+    /// Lays the cells out in padded columns, the header is underlined by a rule.
+    fn table(&mut self, table: Table) {
+        let columns = table.rows.iter().map(Vec::len).max().unwrap_or(0);
+        let width = |cell: &Vec<Span>| cell.iter().map(Span::width).sum::<usize>();
+        let widths: Vec<usize> = (0..columns)
+            .map(|column| {
+                table
+                    .rows
+                    .iter()
+                    .filter_map(|row| row.get(column))
+                    .map(width)
+                    .max()
+                    .unwrap_or(0)
+            })
+            .collect();
+
+        for (i, row) in table.rows.into_iter().enumerate() {
+            let mut spans = vec![];
+            for (column, column_width) in widths.iter().enumerate() {
+                if column > 0 {
+                    spans.push(Span::styled(" │ ", self.style.muted()));
+                }
+                let cell = row.get(column).cloned().unwrap_or_default();
+                let padding = column_width - width(&cell);
+                spans.extend(cell);
+                spans.push(Span::raw(" ".repeat(padding)));
+            }
+            self.line(spans);
+
+            if i == 0 {
+                let rule = widths
+                    .iter()
+                    .map(|width| "─".repeat(*width))
+                    .collect::<Vec<String>>()
+                    .join("─┼─");
+                self.line(vec![Span::styled(rule, self.style.muted())]);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::render;
+    use crate::ui::style::AppStyle;
+
+    fn lines(source: &str) -> Vec<String> {
+        render(source, &AppStyle::default())
+            .lines
+            .iter()
+            .map(|line| line.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn lists_are_marked_and_nested() {
+        let lines = lines("* one\n  1. two\n  2. three\n* four");
+        assert_eq!(lines, vec!["• one", "  1. two", "  2. three", "• four"]);
+    }
+
+    #[test]
+    fn table_columns_line_up() {
+        let lines = lines("| a | bb |\n|---|---|\n| ccc | d |");
+        assert_eq!(lines, vec!["a   │ bb", "────┼───", "ccc │ d "]);
+    }
+}
@@ -1,27 +1,35 @@
 pub mod deck_browser;
+pub mod dialog;
+pub mod editor;
+pub mod heatmap;
+pub mod highlight;
+pub mod home;
 pub mod inputs;
+pub mod markdown;
+pub mod stats;
 pub mod style;
+pub mod terminal;
 use std::path::PathBuf;
 
 use crossterm::event::KeyCode;
 use deck_browser::DeckBrowser;
+use dialog::Dialog;
 use inputs::{Events, Input, KeyMap};
 use ratatui::{
-    layout::{Constraint, Flex, Layout, Rect},
+    layout::{Alignment, Constraint, Flex, Layout, Rect},
     prelude::Backend,
-    text,
-    widgets::{Block, Paragraph, Widget, WidgetRef},
-    Terminal,
+    text::{self, Line, Text},
+    widgets::{Block, Paragraph, Widget, WidgetRef, Wrap},
+    Frame, Terminal,
 };
 use style::AppStyle;
 
+use crate::error::Error;
+use crate::Flashcard;
+
 pub trait KadeuApp {
-    fn handle_input(&mut self, input: Option<&Input>) -> std::io::Result<Exit>;
-    fn render<B: Backend>(
-        &mut self,
-        terminal: &mut Terminal<B>,
-        style: &style::AppStyle,
-    ) -> std::io::Result<()>;
+    fn handle_input(&mut self, input: Option<&Input>) -> std::io::Result<Intent>;
+    fn render(&mut self, frame: &mut Frame, style: &AppStyle);
     // Allow for the app to cleanup anything before the end of it's lifecycle.
     fn drop(&mut self) -> std::io::Result<()> {
         Ok(())
@@ -40,6 +48,17 @@ pub trait KadeuApp {
     fn keymap(&self) -> KeyMap {
         [(KeyCode::Char('q'), Input::Quit)].into()
     }
+
+    /// Called when the app is back on top of the stack, with what the app popped off it left.
+    /// Apps which can't bring themselves up to date can ask to be replaced.
+    fn resume(&mut self, _outcome: Option<Outcome>) -> std::io::Result<Intent> {
+        Ok(Intent::None)
+    }
+
+    /// What the app leaves for the one below it once it is popped.
+    fn outcome(&mut self) -> Option<Outcome> {
+        None
+    }
 }
 
 pub struct AppHandler<B>
@@ -51,32 +70,6 @@ where
     tick: u64,
 }
 
-pub fn run<B: Backend, K: KadeuApp>(
-    terminal: &mut Terminal<B>,
-    app: &mut K,
-    events: Events,
-    tickrate: u64,
-) -> std::io::Result<Exit> {
-    let style = app.style();
-    loop {
-        let input = events.poll(tickrate)?;
-
-        if !app.disable_universal_keymap() {
-            if let Some(Input::Quit) = input {
-                return Ok(Exit::Quit);
-            }
-        }
-
-        let action = app.handle_input(input)?;
-
-        if let Exit::None = action {
-            app.render(terminal, &style)?;
-        } else {
-            return Ok(action);
-        }
-    }
-}
-
 impl<B> AppHandler<B>
 where
     B: Backend,
@@ -85,29 +78,88 @@ where
         self.events = Events::from(keymap)
     }
 
-    pub fn run(&mut self, app: &mut impl KadeuApp) -> std::io::Result<Exit> {
-        self.events = Events::from(app.keymap());
+    /// Runs a stack of screens, the last one given on top, until the bottom one is popped
+    /// or one of them quits. `open` makes the app of each screen asked for,
+    /// anything that goes wrong in an app is shown over it rather than ending the program.
+    pub fn run(
+        &mut self,
+        screens: Vec<Screen>,
+        mut open: impl FnMut(&Screen) -> std::io::Result<Box<dyn KadeuApp>>,
+    ) -> std::io::Result<()> {
         let style = AppStyle::default();
-        loop {
+        let mut stack: Vec<Box<dyn KadeuApp>> = vec![];
+        for screen in &screens {
+            stack.push(opened(open(screen)));
+        }
+        while let Some(app) = stack.last_mut() {
+            // apps may change their keys as they switch modes, e.g. while typing a search
+            self.events = Events::from(app.keymap());
             let input = self.events.poll(self.tick)?;
 
             if !app.disable_universal_keymap() {
                 if let Some(Input::Quit) = input {
-                    return Ok(Exit::Quit);
+                    break;
                 }
             }
 
-            let action = app.handle_input(input)?;
+            let intent = app.handle_input(input.as_ref());
+            navigate(&mut stack, intent, &mut open)?;
+            if let Some(app) = stack.last_mut() {
+                self.terminal.draw(|frame| app.render(frame, &style))?;
+            }
+        }
+        Ok(())
+    }
+}
 
-            if let Exit::None = action {
-                app.render(&mut self.terminal, &style)?;
-            } else {
-                return Ok(action);
+/// Applies an intent to the stack, following on with whatever the app that ends up on top asks for.
+fn navigate(
+    stack: &mut Vec<Box<dyn KadeuApp>>,
+    mut intent: std::io::Result<Intent>,
+    open: &mut impl FnMut(&Screen) -> std::io::Result<Box<dyn KadeuApp>>,
+) -> std::io::Result<()> {
+    loop {
+        match intent {
+            Ok(Intent::None) => return Ok(()),
+            Ok(Intent::Quit) => {
+                stack.clear();
+                return Ok(());
+            }
+            Ok(Intent::Push(screen)) => {
+                stack.push(opened(open(&screen)));
+                return Ok(());
+            }
+            Ok(Intent::Replace(screen)) => {
+                if let Some(mut app) = stack.pop() {
+                    KadeuApp::drop(app.as_mut())?;
+                }
+                stack.push(opened(open(&screen)));
+                return Ok(());
+            }
+            Ok(Intent::Pop) => {
+                let Some(mut app) = stack.pop() else {
+                    return Ok(());
+                };
+                KadeuApp::drop(app.as_mut())?;
+                let outcome = app.outcome();
+                let Some(below) = stack.last_mut() else {
+                    return Ok(());
+                };
+                intent = below.resume(outcome);
+            }
+            Err(error) => {
+                stack.push(Box::new(Dialog::error(&Error::from(error))));
+                return Ok(());
             }
         }
     }
 }
 
+/// The app that was opened, or the reason it couldn't be.
+fn opened(app: std::io::Result<Box<dyn KadeuApp>>) -> Box<dyn KadeuApp> {
+    app.unwrap_or_else(|error| Box::new(Dialog::error(&Error::from(error))))
+}
+
 impl<B> From<Terminal<B>> for AppHandler<B>
 where
     B: Backend,
@@ -121,13 +173,52 @@ where
     }
 }
 
-/// Communicates to the run function if the app would like to exit
-/// and if so in what state it would like to exit (drop and allow the main app to handle the next step?).
-/// None is used to communicate that nothing should be done and the next frame can be rendered.
-pub enum Exit {
-    Drop,
-    Quit,
+/// What an app asks for once it has handled an input.
+pub enum Intent {
+    /// Stay on this screen and draw the next frame.
     None,
+    /// Open a screen on top of this one.
+    Push(Screen),
+    /// Back to the screen below, there is nothing left to run once the last one is popped.
+    Pop,
+    /// Open a screen in place of this one.
+    Replace(Screen),
+    Quit,
+}
+
+/// The screens apps can ask for, made into apps by whoever runs the stack.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Screen {
+    Home,
+    Browse,
+    /// A session of the decks and collections at the paths.
+    Study(Vec<PathBuf>),
+    /// A session of the cards matching a query, titled with the name it is kept under.
+    Query {
+        query: String,
+        name: Option<String>,
+    },
+    /// The editor, opened on the card with this front when there is one.
+    Edit {
+        path: PathBuf,
+        focus: Option<String>,
+    },
+    /// The editor with a new deck which will be saved into the directory.
+    Create(PathBuf),
+    /// The statistics of a deck or collection, or of every deck.
+    Stats(Option<PathBuf>),
+    /// A message over the screen below until it is dismissed.
+    Message {
+        title: String,
+        message: String,
+    },
+}
+
+/// What an app hands back to the one below it when it is popped.
+#[derive(Debug, Clone)]
+pub enum Outcome {
+    /// The card the editor was opened on, as it reads after editing.
+    Edited(Flashcard),
 }
 
 // TODO Debugger needs to be reworked
@@ -173,6 +264,31 @@ impl CardSide {
     }
 }
 
+/// Renders wrapped, centered text into the area.
+/// Text that fits is centered vertically by its wrapped height,
+/// text that doesn't is scrolled by `scroll` lines, which is clamped and returned.
+pub fn render_scrollable(frame: &mut Frame, area: Rect, text: Text, scroll: u16) -> u16 {
+    let paragraph = Paragraph::new(text)
+        .alignment(Alignment::Center)
+        .wrap(Wrap { trim: false });
+    let height = paragraph.line_count(area.width) as u16;
+
+    if height <= area.height {
+        let area = center(area, Constraint::Fill(1), Constraint::Length(height));
+        frame.render_widget(paragraph, area);
+        return 0;
+    }
+
+    let scroll = scroll.min(height - area.height);
+    frame.render_widget(paragraph.scroll((scroll, 0)), area);
+    if scroll + area.height < height {
+        let more = Line::from("▼").right_aligned();
+        let bottom = Rect::new(area.x, area.bottom() - 1, area.width, 1);
+        frame.render_widget(more, bottom);
+    }
+    scroll
+}
+
 pub fn center(area: Rect, horizontal: Constraint, vertical: Constraint) -> Rect {
     let [area] = Layout::horizontal([horizontal])
         .flex(Flex::Center)
@@ -0,0 +1,175 @@
+use crossterm::event::KeyCode;
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::Stylize;
+use ratatui::text::Line;
+use ratatui::widgets::{Bar, BarChart, BarGroup, Borders, Paragraph, Row, Sparkline, Table};
+use ratatui::Frame;
+
+use super::heatmap::Heatmap;
+use super::inputs::{Input, KeyMap};
+use super::style::AppStyle;
+use super::{Intent, KadeuApp};
+use crate::game::stats::{Stats, FORECAST_DAYS, HISTORY_DAYS};
+
+/// Shows the statistics of a deck, or of every deck, on one screen.
+pub struct StatsApp {
+    title: String,
+    stats: Stats,
+    goal: Option<usize>,
+}
+
+impl StatsApp {
+    pub fn new(title: &str, stats: Stats) -> Self {
+        Self {
+            title: title.to_string(),
+            stats,
+            goal: None,
+        }
+    }
+
+    /// Reviews a day which light up the calendar fully.
+    pub fn with_daily_goal(mut self, goal: Option<usize>) -> Self {
+        self.goal = goal;
+        self
+    }
+}
+
+impl KadeuApp for StatsApp {
+    fn handle_input(&mut self, input: Option<&Input>) -> std::io::Result<Intent> {
+        match input {
+            Some(Input::Escape) => Ok(Intent::Pop),
+            _ => Ok(Intent::None),
+        }
+    }
+
+    fn render(&mut self, frame: &mut Frame, style: &AppStyle) {
+        let stats = &self.stats;
+        let percent = |value: Option<f64>| match value {
+            Some(value) => format!("{:.0}%", value * 100.0),
+            None => "-".to_string(),
+        };
+        let seconds = match stats.answer_seconds {
+            Some(seconds) => format!("{:.1}s", seconds),
+            None => "-".to_string(),
+        };
+        let summary = Paragraph::new(vec![
+            Line::from(vec![
+                "Reviews      ".into(),
+                stats.reviews.to_string().bold(),
+            ]),
+            Line::from(vec![
+                "Retention    ".into(),
+                percent(stats.retention).bold(),
+            ]),
+            Line::from(vec!["Answer time  ".into(), seconds.bold()]),
+        ])
+        .block(
+            style
+                .block()
+                .borders(Borders::ALL)
+                .title(self.title.clone()),
+        );
+
+        let per_day: Vec<u64> = stats.per_day.iter().map(|day| day.count as u64).collect();
+        let recent: u64 = per_day.iter().sum();
+        let history = Sparkline::default()
+            .data(&per_day)
+            .style(style.number())
+            .block(style.block().borders(Borders::ALL).title(format!(
+                "{} reviews in the last {} days",
+                recent, HISTORY_DAYS
+            )));
+
+        let maturity = stats.maturity;
+        let bars: Vec<Bar> = [
+            ("new", maturity.new),
+            ("learning", maturity.learning),
+            ("young", maturity.young),
+            ("mature", maturity.mature),
+        ]
+        .into_iter()
+        .map(|(label, count)| Bar::default().label(label.into()).value(count as u64))
+        .collect();
+        let maturity = BarChart::default()
+            .data(BarGroup::default().bars(&bars))
+            .bar_width(8)
+            .bar_gap(2)
+            .bar_style(style.string())
+            .block(style.block().borders(Borders::ALL).title("Cards"));
+
+        let bars: Vec<Bar> = stats
+            .forecast
+            .iter()
+            .map(|day| {
+                Bar::default()
+                    .label(day.date.format("%d").to_string().into())
+                    .value(day.count as u64)
+            })
+            .collect();
+        let forecast = BarChart::default()
+            .data(BarGroup::default().bars(&bars))
+            .bar_width(3)
+            .bar_gap(1)
+            .bar_style(style.keyword())
+            .block(
+                style
+                    .block()
+                    .borders(Borders::ALL)
+                    .title(format!("Due over the next {} days", FORECAST_DAYS)),
+            );
+
+        let rows: Vec<Row> = stats
+            .hardest
+            .iter()
+            .map(|card| {
+                Row::new(vec![
+                    card.misses.to_string(),
+                    card.reviews.to_string(),
+                    card.front.lines().next().unwrap_or_default().to_string(),
+                ])
+            })
+            .collect();
+        let widths = [
+            Constraint::Length(7),
+            Constraint::Length(8),
+            Constraint::Fill(1),
+        ];
+        let hardest = Table::new(rows, widths)
+            .header(Row::new(vec!["Misses", "Reviews", "Card"]).bold())
+            .block(style.block().borders(Borders::ALL).title("Hardest cards"));
+        let footer = Line::styled("q back", style.muted());
+        let calendar = style.block().borders(Borders::ALL).title(format!(
+            "{} day streak, {} at the longest",
+            stats.streak.current, stats.streak.longest
+        ));
+        let heatmap = Heatmap::new(&stats.calendar, style).goal(self.goal);
+
+        let [top, calendar_area, middle, bottom, footer_area] = Layout::vertical([
+            Constraint::Length(5),
+            Constraint::Length(10),
+            Constraint::Length(10),
+            Constraint::Min(4),
+            Constraint::Length(1),
+        ])
+        .areas(frame.area());
+        let [summary_area, history_area] =
+            Layout::horizontal([Constraint::Length(30), Constraint::Fill(1)]).areas(top);
+        let [maturity_area, forecast_area] =
+            Layout::horizontal([Constraint::Length(44), Constraint::Fill(1)]).areas(middle);
+        frame.render_widget(summary, summary_area);
+        frame.render_widget(history, history_area);
+        frame.render_widget(heatmap, calendar.inner(calendar_area));
+        frame.render_widget(calendar, calendar_area);
+        frame.render_widget(maturity, maturity_area);
+        frame.render_widget(forecast, forecast_area);
+        frame.render_widget(hardest, bottom);
+        frame.render_widget(Paragraph::new(footer), footer_area);
+    }
+
+    fn keymap(&self) -> KeyMap {
+        let mut map = KeyMap::new();
+        map.insert(KeyCode::Char('q'), Input::Escape);
+        map.insert(KeyCode::Esc, Input::Escape);
+        map
+    }
+}
@@ -11,6 +11,13 @@ pub struct AppStyle {
     background_color: Color,
     border_color: Color,
     text_color: Color,
+    code_color: Color,
+    muted_color: Color,
+    keyword_color: Color,
+    string_color: Color,
+    number_color: Color,
+    function_color: Color,
+    type_color: Color,
 }
 
 impl Default for AppStyle {
@@ -20,6 +27,13 @@ impl Default for AppStyle {
             // Still needs work
             border_color: Color::White,
             text_color: Color::White,
+            code_color: Color::Yellow,
+            muted_color: Color::DarkGray,
+            keyword_color: Color::Magenta,
+            string_color: Color::Green,
+            number_color: Color::Cyan,
+            function_color: Color::Blue,
+            type_color: Color::LightYellow,
         }
     }
 }
@@ -35,6 +49,47 @@ impl AppStyle {
             .border_style(Style::new().fg(self.border_color))
     }
 
+    /// Inline code and code blocks.
+    pub fn code(&self) -> Style {
+        Style::new().fg(self.code_color)
+    }
+
+    /// Decorations such as rules and quote bars which shouldn't draw the eye.
+    pub fn muted(&self) -> Style {
+        Style::new().fg(self.muted_color)
+    }
+
+    pub fn keyword(&self) -> Style {
+        Style::new().fg(self.keyword_color)
+    }
+
+    pub fn string(&self) -> Style {
+        Style::new().fg(self.string_color)
+    }
+
+    pub fn number(&self) -> Style {
+        Style::new().fg(self.number_color)
+    }
+
+    pub fn function(&self) -> Style {
+        Style::new().fg(self.function_color)
+    }
+
+    /// Types, lifetimes and shell variables.
+    pub fn type_name(&self) -> Style {
+        Style::new().fg(self.type_color)
+    }
+
+    /// Days of the activity calendar, from 0 for a day without reviews up to 4 for the busiest.
+    pub fn heat(&self, level: u8) -> Style {
+        match level {
+            0 => self.muted(),
+            1 => self.string().dim(),
+            2 => self.string(),
+            _ => self.string().bold(),
+        }
+    }
+
     fn text<T: fmt::Display>(&self, item: T) -> Text<'_> {
         Text::from(item.to_string()).style(Style::new().fg(self.text_color))
     }
@@ -0,0 +1,42 @@
+use std::io::{self, stdout};
+use std::panic;
+
+use crossterm::cursor::Show;
+use crossterm::execute;
+use crossterm::terminal::{
+    disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen,
+};
+
+/// Raw mode and the alternate screen for as long as the guard is kept.
+/// Dropping it, returning early with an error or panicking hands the shell back as it was.
+pub struct TerminalGuard {
+    _private: (),
+}
+
+impl TerminalGuard {
+    pub fn enter() -> io::Result<Self> {
+        let hook = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            // the panic message would be lost on the alternate screen
+            restore();
+            hook(info);
+        }));
+        // made first so that a failure halfway is undone as well
+        let guard = Self { _private: () };
+        enable_raw_mode()?;
+        execute!(stdout(), EnterAlternateScreen)?;
+        Ok(guard)
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        restore();
+    }
+}
+
+/// Puts the terminal back, whatever state it was left in.
+fn restore() {
+    let _ = disable_raw_mode();
+    let _ = execute!(stdout(), LeaveAlternateScreen, Show);
+}